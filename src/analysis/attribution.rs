@@ -0,0 +1,265 @@
+use crate::parsers::base::BaseMessage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Sizes of the character n-grams used as features.
+const NGRAM_SIZES: [usize; 3] = [2, 3, 4];
+/// Laplace smoothing of the n-gram counts.
+const ALPHA: f64 = 1.0;
+/// Features seen less often are ignored when listing the most discriminative ones.
+const MIN_FEATURE_COUNT: f64 = 5.0;
+/// One text out of `DEFAULT_TEST_EVERY` is held out to measure the accuracy.
+pub const DEFAULT_TEST_EVERY: usize = 5;
+
+/// Lowercased character n-grams of a text, padded with spaces so that the first and last
+/// letters of the message make their own features.
+pub fn get_char_ngrams(text: &str) -> Vec<String> {
+    let chars: Vec<char> = format!(" {} ", text.trim().to_lowercase())
+        .chars()
+        .collect();
+    let mut ngrams: Vec<String> = Vec::new();
+
+    for size in NGRAM_SIZES {
+        for window in chars.windows(size) {
+            ngrams.push(window.iter().collect());
+        }
+    }
+    return ngrams;
+}
+
+/// Multinomial naive Bayes on character n-grams: who is the most likely author of a text.
+#[derive(Clone, Debug, Default)]
+pub struct AttributionModel {
+    log_priors: HashMap<String, f64>,
+    /// {participant: {n-gram: count}}
+    counts: HashMap<String, HashMap<String, f64>>,
+    totals: HashMap<String, f64>,
+    vocabulary_size: f64,
+}
+
+impl AttributionModel {
+    pub fn train<'a>(messages: impl Iterator<Item = &'a BaseMessage>) -> AttributionModel {
+        let mut model = AttributionModel::default();
+        let mut num_texts: HashMap<String, f64> = HashMap::new();
+        let mut vocabulary: HashMap<String, ()> = HashMap::new();
+
+        for msg in messages {
+            let content = match &msg.content {
+                Some(content) if !content.trim().is_empty() => content,
+                _ => continue,
+            };
+            *num_texts.entry(msg.sender_name.clone()).or_insert(0.0) += 1.0;
+            let counts = model.counts.entry(msg.sender_name.clone()).or_default();
+            let total = model.totals.entry(msg.sender_name.clone()).or_insert(0.0);
+            for ngram in get_char_ngrams(content) {
+                *counts.entry(ngram.clone()).or_insert(0.0) += 1.0;
+                *total += 1.0;
+                vocabulary.insert(ngram, ());
+            }
+        }
+
+        let all_texts: f64 = num_texts.values().sum();
+        model.log_priors = num_texts
+            .into_iter()
+            .map(|(name, count)| (name, (count / all_texts).ln()))
+            .collect();
+        model.vocabulary_size = vocabulary.len() as f64;
+        return model;
+    }
+
+    fn log_likelihood(&self, name: &str, ngram: &str) -> f64 {
+        let count = self.counts[name].get(ngram).copied().unwrap_or(0.0);
+        return ((count + ALPHA) / (self.totals[name] + ALPHA * self.vocabulary_size)).ln();
+    }
+
+    /// Probability that each participant wrote the text, most likely first.
+    pub fn predict(&self, text: &str) -> Vec<(String, f64)> {
+        let ngrams = get_char_ngrams(text);
+        let scores: Vec<(String, f64)> = self
+            .log_priors
+            .iter()
+            .map(|(name, log_prior)| {
+                let score = log_prior
+                    + ngrams
+                        .iter()
+                        .map(|ngram| self.log_likelihood(name, ngram))
+                        .sum::<f64>();
+                (name.clone(), score)
+            })
+            .collect();
+
+        // Softmax, shifted by the best score to avoid underflows.
+        let best_score = scores
+            .iter()
+            .map(|(_, score)| *score)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let sum: f64 = scores
+            .iter()
+            .map(|(_, score)| (score - best_score).exp())
+            .sum();
+        let mut probabilities: Vec<(String, f64)> = scores
+            .into_iter()
+            .map(|(name, score)| (name, (score - best_score).exp() / sum))
+            .collect();
+        probabilities.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        return probabilities;
+    }
+
+    /// N-grams most typical of each participant: log ratio of their frequency in the texts
+    /// of the participant and in the texts of everybody else.
+    pub fn get_discriminative_features(
+        &self,
+        num_features: usize,
+    ) -> HashMap<String, Vec<(String, f64)>> {
+        let mut all_counts: HashMap<&String, f64> = HashMap::new();
+        for counts in self.counts.values() {
+            for (ngram, count) in counts.iter() {
+                *all_counts.entry(ngram).or_insert(0.0) += count;
+            }
+        }
+        let all_total: f64 = self.totals.values().sum();
+
+        let mut features: HashMap<String, Vec<(String, f64)>> = HashMap::new();
+        for (name, counts) in self.counts.iter() {
+            let other_total = all_total - self.totals[name];
+            let mut scores: Vec<(String, f64)> = counts
+                .iter()
+                .filter(|(_, count)| **count >= MIN_FEATURE_COUNT)
+                .map(|(ngram, count)| {
+                    let other_count = all_counts[ngram] - count;
+                    let frequency = (count + ALPHA) / (self.totals[name] + ALPHA);
+                    let other_frequency = (other_count + ALPHA) / (other_total + ALPHA);
+                    (ngram.clone(), (frequency / other_frequency).ln())
+                })
+                .collect();
+            scores.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            scores.truncate(num_features);
+            features.insert(name.clone(), scores);
+        }
+        return features;
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AttributionStats {
+    /// Share of the held out texts attributed to the right participant.
+    pub accuracy: f64,
+    /// Share of the held out texts of each participant recognised as theirs.
+    pub participant_accuracy: HashMap<String, f64>,
+    pub num_tested: usize,
+    /// Most discriminative character n-grams of each participant, with their log ratio.
+    pub features: HashMap<String, Vec<(String, f64)>>,
+}
+
+/// Train on all the texts but one out of `test_every` and measure the accuracy on those.
+/// The features come from a model trained on every text.
+pub fn get_attribution_stats(
+    messages: &Vec<BaseMessage>,
+    test_every: usize,
+    num_features: usize,
+) -> AttributionStats {
+    let texts: Vec<&BaseMessage> = messages
+        .iter()
+        .filter(|msg| {
+            msg.content
+                .as_deref()
+                .is_some_and(|text| !text.trim().is_empty())
+        })
+        .collect();
+    let test_every = test_every.max(2);
+    let model = AttributionModel::train(
+        texts
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| idx % test_every != 0)
+            .map(|(_, msg)| *msg),
+    );
+
+    let mut tested: HashMap<&String, (f64, f64)> = HashMap::new();
+    for msg in texts.iter().step_by(test_every) {
+        let content = msg.content.as_deref().unwrap_or_default();
+        let right = match model.predict(content).first() {
+            Some((name, _)) if *name == msg.sender_name => 1.0,
+            _ => 0.0,
+        };
+        let counts = tested.entry(&msg.sender_name).or_insert((0.0, 0.0));
+        counts.0 += 1.0;
+        counts.1 += right;
+    }
+
+    let num_tested: f64 = tested.values().map(|counts| counts.0).sum();
+    let num_right: f64 = tested.values().map(|counts| counts.1).sum();
+    return AttributionStats {
+        accuracy: if num_tested > 0.0 {
+            num_right / num_tested
+        } else {
+            0.0
+        },
+        participant_accuracy: tested
+            .iter()
+            .map(|(name, (count, right))| (name.to_string(), right / count))
+            .collect(),
+        num_tested: num_tested as usize,
+        features: AttributionModel::train(texts.into_iter())
+            .get_discriminative_features(num_features),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_attribution_stats, get_char_ngrams, AttributionModel};
+    use crate::analysis::fixtures::message;
+    use crate::parsers::base::BaseMessage;
+
+    fn messages() -> Vec<BaseMessage> {
+        let p1 = [
+            "mdr trop bien",
+            "ouais mdr",
+            "grave mdr",
+            "mdr ouais",
+            "trop bien mdr",
+        ];
+        let p2 = [
+            "Sounds good!",
+            "Good night!",
+            "Sounds great!",
+            "Great news!",
+            "Good!",
+        ];
+        p1.iter()
+            .zip(p2.iter())
+            .flat_map(|(text_1, text_2)| [("p1", text_1), ("p2", text_2)])
+            .map(|(sender, content)| message(sender, 0, Some(content)))
+            .collect()
+    }
+
+    #[test]
+    fn test_char_ngrams() {
+        assert_eq!(
+            get_char_ngrams("Ok!"),
+            vec![" o", "ok", "k!", "! ", " ok", "ok!", "k! ", " ok!", "ok! "]
+        );
+    }
+
+    #[test]
+    fn test_attribution_model() {
+        let messages = messages();
+        let model = AttributionModel::train(messages.iter());
+
+        let prediction = model.predict("mdr bien");
+        assert_eq!(prediction[0].0, "p1");
+        assert!(prediction[0].1 > 0.9);
+        let total: f64 = prediction.iter().map(|(_, probability)| probability).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert_eq!(model.predict("good news")[0].0, "p2");
+
+        let features = model.get_discriminative_features(3);
+        assert!(features["p1"].iter().any(|(ngram, _)| ngram.contains("md")));
+
+        // Texts 0, 3, 6 and 9 are held out, two of each participant.
+        let stats = get_attribution_stats(&messages, 3, 3);
+        assert_eq!(stats.num_tested, 4);
+        assert_eq!(stats.accuracy, 1.0);
+        assert_eq!(stats.participant_accuracy["p1"], 1.0);
+    }
+}
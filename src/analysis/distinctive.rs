@@ -0,0 +1,189 @@
+use crate::analysis::stopwords::StopWords;
+use crate::analysis::tokenizer::{get_ngrams, tokenize, TokenizerOptions};
+use crate::parsers::base::BaseMessage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Weight of the informative prior of the log-odds ratio, as a fraction of the
+/// term counts over the whole conversation.
+const PRIOR_STRENGTH: f64 = 0.1;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DistinctiveTerms {
+    /// Terms ranked by TF-IDF (participants being the documents), per participant.
+    pub tf_idf: HashMap<String, Vec<(String, f64)>>,
+    /// Terms ranked by the z-score of their weighted log-odds ratio against the rest
+    /// of the conversation, with an informative Dirichlet prior, per participant.
+    pub log_odds: HashMap<String, Vec<(String, f64)>>,
+}
+
+/// Count the terms (words or n-grams) of each participant.
+/// N-grams made only of stop words are ignored, like stop words for single words.
+pub fn get_term_counts(
+    messages: &Vec<BaseMessage>,
+    stop_words: &StopWords,
+    ngram_size: usize,
+) -> HashMap<String, HashMap<String, f64>> {
+    let mut term_counts: HashMap<String, HashMap<String, f64>> = HashMap::new();
+    let tokenizer_options = TokenizerOptions::default();
+
+    for msg in messages {
+        if let Some(content) = &msg.content {
+            let tokens = tokenize(content, &tokenizer_options);
+            let counts = term_counts.entry(msg.sender_name.clone()).or_default();
+
+            for term in get_ngrams(&tokens, ngram_size) {
+                if term.split(' ').all(|word| stop_words.contains(word)) {
+                    continue;
+                }
+                *counts.entry(term).or_insert(0.0) += 1.0;
+            }
+        }
+    }
+
+    return term_counts;
+}
+
+fn top_terms(scores: HashMap<String, f64>, num_terms: usize) -> Vec<(String, f64)> {
+    let mut scores: Vec<(String, f64)> = scores.into_iter().collect();
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    scores.truncate(num_terms);
+    return scores;
+}
+
+fn get_tf_idf(
+    term_counts: &HashMap<String, HashMap<String, f64>>,
+    num_terms: usize,
+) -> HashMap<String, Vec<(String, f64)>> {
+    let num_documents = term_counts.len() as f64;
+    let mut document_frequencies: HashMap<&String, f64> = HashMap::new();
+    for counts in term_counts.values() {
+        for term in counts.keys() {
+            *document_frequencies.entry(term).or_insert(0.0) += 1.0;
+        }
+    }
+
+    let mut tf_idf = HashMap::new();
+    for (name, counts) in term_counts.iter() {
+        let total: f64 = counts.values().sum();
+        let scores = counts
+            .iter()
+            .map(|(term, count)| {
+                // Smoothed idf, shared terms keep a (lower) score.
+                let idf = ((1.0 + num_documents) / (1.0 + document_frequencies[term])).ln() + 1.0;
+                (term.clone(), count / total * idf)
+            })
+            .collect();
+        tf_idf.insert(name.clone(), top_terms(scores, num_terms));
+    }
+
+    return tf_idf;
+}
+
+/// Monroe, Colaresi & Quinn (2008), "Fightin' Words": log-odds ratio of each term between
+/// a participant and everyone else, with a Dirichlet prior built from the conversation itself.
+fn get_log_odds(
+    term_counts: &HashMap<String, HashMap<String, f64>>,
+    num_terms: usize,
+) -> HashMap<String, Vec<(String, f64)>> {
+    let mut all_counts: HashMap<&String, f64> = HashMap::new();
+    for counts in term_counts.values() {
+        for (term, count) in counts.iter() {
+            *all_counts.entry(term).or_insert(0.0) += count;
+        }
+    }
+    let total_all: f64 = all_counts.values().sum();
+    let prior_total = PRIOR_STRENGTH * total_all;
+
+    let mut log_odds = HashMap::new();
+    for (name, counts) in term_counts.iter() {
+        let total: f64 = counts.values().sum();
+        let total_rest = total_all - total;
+
+        let scores = counts
+            .iter()
+            .map(|(term, count)| {
+                let count_rest = all_counts[term] - count;
+                let prior = PRIOR_STRENGTH * all_counts[term];
+
+                let log_odds_participant =
+                    ((count + prior) / (total + prior_total - count - prior)).ln();
+                let log_odds_rest =
+                    ((count_rest + prior) / (total_rest + prior_total - count_rest - prior)).ln();
+                let variance = 1.0 / (count + prior) + 1.0 / (count_rest + prior);
+
+                (
+                    term.clone(),
+                    (log_odds_participant - log_odds_rest) / variance.sqrt(),
+                )
+            })
+            .collect();
+        log_odds.insert(name.clone(), top_terms(scores, num_terms));
+    }
+
+    return log_odds;
+}
+
+/// Terms (words when `ngram_size` is 1, bigrams when 2...) that distinguish each participant
+/// from the rest of the conversation, best first.
+pub fn get_distinctive_terms(
+    messages: &Vec<BaseMessage>,
+    num_terms: usize,
+    stop_words: &StopWords,
+    ngram_size: usize,
+) -> DistinctiveTerms {
+    let term_counts = get_term_counts(messages, stop_words, ngram_size);
+
+    return DistinctiveTerms {
+        tf_idf: get_tf_idf(&term_counts, num_terms),
+        log_odds: get_log_odds(&term_counts, num_terms),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_distinctive_terms;
+    use crate::analysis::fixtures::messages_from;
+    use crate::analysis::stopwords::{Language, StopWords};
+
+    #[test]
+    fn test_distinctive_words() {
+        let messages = messages_from(&[
+            ("p1", "hello, pizza tonight?"),
+            ("p2", "hello! sushi tonight"),
+            ("p1", "pizza pizza hello"),
+            ("p2", "no, sushi"),
+            ("p1", "ok hello"),
+            ("p2", "hello hello sushi"),
+        ]);
+        let stop_words = StopWords::new(&[Language::English]);
+        let terms = get_distinctive_terms(&messages, 3, &stop_words, 1);
+
+        assert_eq!(terms.tf_idf["p1"][0].0, "pizza");
+        assert_eq!(terms.tf_idf["p2"][0].0, "sushi");
+        assert_eq!(terms.log_odds["p1"][0].0, "pizza");
+        assert!(terms.log_odds["p1"][0].1 > 0.0);
+        assert_eq!(terms.log_odds["p2"][0].0, "sushi");
+
+        // "hello" is used as much by both participants, it is not distinctive.
+        let hello = terms.log_odds["p1"]
+            .iter()
+            .find(|x| x.0 == "hello")
+            .unwrap();
+        assert!(hello.1.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_distinctive_bigrams() {
+        let messages = messages_from(&[
+            ("p1", "love you too"),
+            ("p1", "love you too!"),
+            ("p2", "see you tomorrow"),
+        ]);
+        let stop_words = StopWords::new(&[Language::English]);
+        let terms = get_distinctive_terms(&messages, 2, &stop_words, 2);
+
+        assert_eq!(terms.log_odds["p1"][0].0, "love you");
+        assert_eq!(terms.log_odds["p2"][0].0, "see you");
+    }
+}
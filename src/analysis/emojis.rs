@@ -0,0 +1,156 @@
+use crate::parsers::base::BaseMessage;
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use unicode_segmentation::UnicodeSegmentation;
+
+const VARIATION_SELECTOR_EMOJI: char = '\u{FE0F}';
+const COMBINING_KEYCAP: char = '\u{20E3}';
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct EmojiStats {
+    /// Most used emojis per participant with their count, most used first.
+    pub top_emojis: HashMap<String, Vec<(String, i64)>>,
+    /// Average number of emojis in a message, per participant.
+    pub emojis_per_message: HashMap<String, f64>,
+    /// Number of emojis sent each month (%Y-%m), per participant.
+    pub usage_over_time: HashMap<String, BTreeMap<String, i64>>,
+}
+
+/// Symbols displayed as emojis by default (Emoji_Presentation). Text symbols such as ✓,
+/// ★ or © only are emojis when followed by U+FE0F.
+fn is_pictographic(c: char) -> bool {
+    return matches!(c as u32,
+        0x1F000..=0x1FAFF // Cards, enclosed alphanumerics, pictographs, emoticons, transport...
+        | 0x231A..=0x231B
+        | 0x23E9..=0x23EC
+        | 0x23F0
+        | 0x23F3
+        | 0x25FD..=0x25FE
+        // Miscellaneous symbols and dingbats
+        | 0x2614..=0x2615
+        | 0x2648..=0x2653
+        | 0x267F
+        | 0x2693
+        | 0x26A1
+        | 0x26AA..=0x26AB
+        | 0x26BD..=0x26BE
+        | 0x26C4..=0x26C5
+        | 0x26CE
+        | 0x26D4
+        | 0x26EA
+        | 0x26F2..=0x26F3
+        | 0x26F5
+        | 0x26FA
+        | 0x26FD
+        | 0x2705
+        | 0x270A..=0x270B
+        | 0x2728
+        | 0x274C
+        | 0x274E
+        | 0x2753..=0x2755
+        | 0x2757
+        | 0x2795..=0x2797
+        | 0x27B0
+        | 0x27BF
+        | 0x2B1B..=0x2B1C
+        | 0x2B50
+        | 0x2B55
+    );
+}
+
+/// Whether an extended grapheme cluster is an emoji. This handles ZWJ sequences,
+/// skin tones and flags (they are a single grapheme) as well as keycaps and
+/// symbols explicitly requested in emoji presentation.
+pub fn is_emoji(grapheme: &str) -> bool {
+    return grapheme
+        .chars()
+        .any(|c| is_pictographic(c) || c == VARIATION_SELECTOR_EMOJI || c == COMBINING_KEYCAP);
+}
+
+/// All the emojis of a text, one item per extended grapheme cluster.
+pub fn get_emojis(text: &str) -> Vec<&str> {
+    return text.graphemes(true).filter(|g| is_emoji(g)).collect();
+}
+
+/// Remove every emoji from a text.
+pub fn strip_emojis(text: &str) -> String {
+    return text.graphemes(true).filter(|g| !is_emoji(g)).collect();
+}
+
+pub fn get_emoji_stats(messages: &Vec<BaseMessage>, num_emojis: usize) -> EmojiStats {
+    let mut emoji_counts: HashMap<String, HashMap<String, i64>> = HashMap::new();
+    let mut messages_count: HashMap<String, i64> = HashMap::new();
+    let mut stats = EmojiStats::default();
+
+    for msg in messages {
+        if let Some(content) = &msg.content {
+            *messages_count.entry(msg.sender_name.clone()).or_insert(0) += 1;
+            let emojis = get_emojis(content);
+            if emojis.is_empty() {
+                continue;
+            }
+
+            let counts = emoji_counts.entry(msg.sender_name.clone()).or_default();
+            for emoji in emojis.iter() {
+                *counts.entry(emoji.to_string()).or_insert(0) += 1;
+            }
+
+            if let Some(datetime) = DateTime::from_timestamp_millis(msg.timestamp_ms) {
+                *stats
+                    .usage_over_time
+                    .entry(msg.sender_name.clone())
+                    .or_default()
+                    .entry(datetime.format("%Y-%m").to_string())
+                    .or_insert(0) += emojis.len() as i64;
+            }
+        }
+    }
+
+    for (name, num_messages) in messages_count.iter() {
+        let counts = emoji_counts.remove(name).unwrap_or_default();
+        let total: i64 = counts.values().sum();
+
+        let mut top_emojis: Vec<(String, i64)> = counts.into_iter().collect();
+        top_emojis.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_emojis.truncate(num_emojis);
+
+        stats.top_emojis.insert(name.clone(), top_emojis);
+        stats
+            .emojis_per_message
+            .insert(name.clone(), total as f64 / *num_messages as f64);
+    }
+
+    return stats;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_emoji_stats, get_emojis, strip_emojis};
+    use crate::analysis::fixtures::message;
+
+    #[test]
+    fn test_emojis_graphemes() {
+        // Family (ZWJ sequence), skin tone, flag, keycap and a plain heart.
+        let text = "salut 👩‍👩‍👧 👍🏽 🇫🇷 1️⃣ ❤️ 2 #";
+        assert_eq!(get_emojis(text), vec!["👩‍👩‍👧", "👍🏽", "🇫🇷", "1️⃣", "❤️"]);
+        assert_eq!(strip_emojis("ok😂!"), "ok!");
+        assert_eq!(get_emojis("✓ done ★ © ™ 5° ⚡ ✔️"), vec!["⚡", "✔️"]);
+    }
+
+    #[test]
+    fn test_emoji_stats() {
+        let messages = vec![
+            message("p1", 1688067261456, Some("😂😂 lol 👍")),
+            message("p1", 1688067261457, Some("no emoji")),
+            message("p2", 1688067261458, Some("nothing")),
+        ];
+        let stats = get_emoji_stats(&messages, 1);
+
+        assert_eq!(stats.top_emojis["p1"], vec![("😂".to_string(), 2)]);
+        assert!(stats.top_emojis["p2"].is_empty());
+        assert_eq!(stats.emojis_per_message["p1"], 1.5);
+        assert_eq!(stats.emojis_per_message["p2"], 0.0);
+        assert_eq!(stats.usage_over_time["p1"]["2023-06"], 3);
+    }
+}
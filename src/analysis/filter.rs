@@ -0,0 +1,234 @@
+use crate::analysis::media::get_links;
+use crate::analysis::unanswered::is_question;
+use crate::parsers::base::BaseMessage;
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Weekday};
+use regex::Regex;
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageKind {
+    /// Any message with some text.
+    Text,
+    /// Text containing a web link (see `get_links`).
+    Link,
+    /// Text asking a question (see `is_question`).
+    Question,
+    /// Messages without text: photos, stickers, calls...
+    Media,
+}
+
+impl MessageKind {
+    pub const ALL: [MessageKind; 4] = [
+        MessageKind::Text,
+        MessageKind::Link,
+        MessageKind::Question,
+        MessageKind::Media,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            MessageKind::Text => "text",
+            MessageKind::Link => "link",
+            MessageKind::Question => "question",
+            MessageKind::Media => "media",
+        };
+    }
+
+    pub fn from_name(name: &str) -> Option<MessageKind> {
+        return MessageKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name.to_lowercase());
+    }
+
+    fn matches(&self, content: Option<&str>) -> bool {
+        return match (self, content) {
+            (MessageKind::Text, Some(_)) => true,
+            (MessageKind::Link, Some(text)) => !get_links(text).is_empty(),
+            (MessageKind::Question, Some(text)) => is_question(text),
+            (MessageKind::Media, None) => true,
+            _ => false,
+        };
+    }
+}
+
+/// Conditions a message must meet to be analysed, `None` (the default) keeps everything.
+/// Dates and hours are in UTC like the rest of the analyses.
+#[derive(Clone, Debug, Default)]
+pub struct MessageFilter {
+    /// First day kept, inclusive.
+    pub since: Option<NaiveDate>,
+    /// Last day kept, inclusive.
+    pub until: Option<NaiveDate>,
+    pub weekdays: Option<HashSet<Weekday>>,
+    /// Hours kept, from the first (inclusive) to the second (exclusive). Wraps around
+    /// midnight when the first is greater: (22, 2) keeps 22:00 to 01:59.
+    pub hours: Option<(u32, u32)>,
+    /// Senders kept.
+    pub participants: Option<HashSet<String>>,
+    pub kind: Option<MessageKind>,
+    /// Messages whose text matches the expression, messages without text never match.
+    pub content: Option<Regex>,
+    /// Minimum number of characters of the text.
+    pub min_length: Option<usize>,
+}
+
+impl MessageFilter {
+    pub fn keeps_participant(&self, name: &str) -> bool {
+        return match &self.participants {
+            Some(participants) => participants.contains(name),
+            None => true,
+        };
+    }
+
+    fn keeps_time(&self, timestamp_ms: i64) -> bool {
+        if self.since.is_none()
+            && self.until.is_none()
+            && self.weekdays.is_none()
+            && self.hours.is_none()
+        {
+            return true;
+        }
+        let datetime = match DateTime::from_timestamp_millis(timestamp_ms) {
+            Some(datetime) => datetime,
+            None => return false,
+        };
+
+        let date = datetime.date_naive();
+        if self.since.is_some_and(|since| date < since)
+            || self.until.is_some_and(|until| date > until)
+        {
+            return false;
+        }
+        if let Some(weekdays) = &self.weekdays {
+            if !weekdays.contains(&datetime.weekday()) {
+                return false;
+            }
+        }
+        if let Some((start, end)) = self.hours {
+            let hour = datetime.hour();
+            let in_window = if start <= end {
+                hour >= start && hour < end
+            } else {
+                hour >= start || hour < end
+            };
+            if !in_window {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    fn keeps_content(&self, content: Option<&str>) -> bool {
+        if self.kind.is_some_and(|kind| !kind.matches(content)) {
+            return false;
+        }
+        if let Some(regex) = &self.content {
+            if !content.is_some_and(|text| regex.is_match(text)) {
+                return false;
+            }
+        }
+        if let Some(min_length) = self.min_length {
+            if content.map_or(0, |text| text.chars().count()) < min_length {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    /// Takes the fields rather than a `BaseMessage` so that the messages of every platform
+    /// can be filtered before being converted.
+    pub fn matches(&self, sender_name: &str, timestamp_ms: i64, content: Option<&str>) -> bool {
+        return self.keeps_participant(sender_name)
+            && self.keeps_time(timestamp_ms)
+            && self.keeps_content(content);
+    }
+
+    /// Filtered copy of the messages, in the same order.
+    pub fn apply(&self, messages: &Vec<BaseMessage>) -> Vec<BaseMessage> {
+        return messages
+            .iter()
+            .filter(|msg| self.matches(&msg.sender_name, msg.timestamp_ms, msg.content.as_deref()))
+            .cloned()
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MessageFilter, MessageKind};
+    use crate::analysis::fixtures::{message, timestamp};
+    use crate::parsers::base::BaseMessage;
+    use chrono::{NaiveDate, Weekday};
+    use regex::Regex;
+
+    fn senders(filter: &MessageFilter, messages: &Vec<BaseMessage>) -> Vec<String> {
+        filter
+            .apply(messages)
+            .iter()
+            .map(|msg| msg.sender_name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_message_filter() {
+        let messages = vec![
+            // Saturday night
+            message(
+                "p1",
+                timestamp("2022-12-31T23:30:00Z"),
+                Some("https://example.com"),
+            ),
+            // Monday morning
+            message("p2", timestamp("2023-01-02T08:00:00Z"), Some("ok")),
+            // Thursday night
+            message("p3", timestamp("2023-06-15T23:00:00Z"), None),
+            // Monday noon
+            message(
+                "p4",
+                timestamp("2024-01-01T12:00:00Z"),
+                Some("Did you see it?"),
+            ),
+        ];
+
+        assert_eq!(MessageFilter::default().apply(&messages).len(), 4);
+
+        let year_2023 = MessageFilter {
+            since: NaiveDate::from_ymd_opt(2023, 1, 1),
+            until: NaiveDate::from_ymd_opt(2023, 12, 31),
+            ..MessageFilter::default()
+        };
+        assert_eq!(senders(&year_2023, &messages), vec!["p2", "p3"]);
+
+        let mondays = MessageFilter {
+            weekdays: Some([Weekday::Mon].into_iter().collect()),
+            ..MessageFilter::default()
+        };
+        assert_eq!(senders(&mondays, &messages), vec!["p2", "p4"]);
+
+        let nights = MessageFilter {
+            hours: Some((22, 9)),
+            ..MessageFilter::default()
+        };
+        assert_eq!(senders(&nights, &messages), vec!["p1", "p2", "p3"]);
+
+        let links = MessageFilter {
+            kind: Some(MessageKind::Link),
+            ..MessageFilter::default()
+        };
+        assert_eq!(senders(&links, &messages), vec!["p1"]);
+        let media = MessageFilter {
+            kind: MessageKind::from_name("Media"),
+            ..MessageFilter::default()
+        };
+        assert_eq!(senders(&media, &messages), vec!["p3"]);
+
+        let combined = MessageFilter {
+            participants: Some(["p2".to_string(), "p4".to_string()].into_iter().collect()),
+            content: Regex::new("(?i)^did").ok(),
+            min_length: Some(5),
+            ..MessageFilter::default()
+        };
+        assert_eq!(senders(&combined, &messages), vec!["p4"]);
+        assert!(!combined.keeps_participant("p1"));
+    }
+}
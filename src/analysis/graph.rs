@@ -0,0 +1,478 @@
+use crate::parsers::base::BaseMessage;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Probability of following an edge rather than jumping anywhere in PageRank.
+const DAMPING: f64 = 0.85;
+const PAGERANK_ITERATIONS: usize = 100;
+const LAYOUT_ITERATIONS: usize = 200;
+
+/// Who talks to whom: `source` answered `target` (wrote right after them) or mentioned them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InteractionEdge {
+    pub source: String,
+    pub target: String,
+    pub replies: i64,
+    /// "@name" mentions of the target in the messages of the source.
+    pub mentions: i64,
+}
+
+impl InteractionEdge {
+    pub fn weight(&self) -> i64 {
+        return self.replies + self.mentions;
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GraphNode {
+    pub name: String,
+    pub messages: i64,
+    /// Weight of the edges towards this participant: how often others answer them.
+    pub in_strength: i64,
+    /// Weight of the edges from this participant: how often they answer others.
+    pub out_strength: i64,
+    /// Share of the other participants they interact with, in either direction.
+    pub degree_centrality: f64,
+    /// Weighted PageRank, high for participants the central participants answer to.
+    pub pagerank: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct InteractionGraph {
+    /// Sorted by name.
+    pub nodes: Vec<GraphNode>,
+    /// Sorted by source then target.
+    pub edges: Vec<InteractionEdge>,
+}
+
+/// Participants mentioned in a text as "@Full Name" or "@FirstName", case insensitive.
+/// The longest matching name wins so that "@Anna Lee" is not read as "@Anna", and full
+/// names win over first names.
+pub fn get_mentions<'a>(text: &str, participants: &'a [String]) -> Vec<&'a String> {
+    let text = text.to_lowercase();
+    let mut mentions: Vec<&String> = Vec::new();
+
+    for (idx, _) in text.match_indices('@') {
+        let mentioned = &text[idx + 1..];
+        let found = participants
+            .iter()
+            .flat_map(|name| {
+                let full_name = name.to_lowercase();
+                let first_name = full_name.split_whitespace().next().unwrap_or_default();
+                [
+                    (name, full_name.clone(), true),
+                    (name, first_name.to_string(), false),
+                ]
+            })
+            .filter(|(_, alias, _)| {
+                !alias.is_empty()
+                    && mentioned.starts_with(alias.as_str())
+                    && !mentioned[alias.len()..]
+                        .chars()
+                        .next()
+                        .is_some_and(char::is_alphanumeric)
+            })
+            .max_by_key(|(_, alias, is_full_name)| (alias.len(), *is_full_name));
+        if let Some((name, _, _)) = found {
+            mentions.push(name);
+        }
+    }
+
+    return mentions;
+}
+
+fn get_pagerank(names: &[String], edges: &Vec<InteractionEdge>) -> HashMap<String, f64> {
+    let num_nodes = names.len() as f64;
+    let mut out_weights: HashMap<&String, f64> = HashMap::new();
+    for edge in edges {
+        *out_weights.entry(&edge.source).or_insert(0.0) += edge.weight() as f64;
+    }
+
+    let mut ranks: HashMap<String, f64> = names
+        .iter()
+        .map(|name| (name.clone(), 1.0 / num_nodes))
+        .collect();
+    for _ in 0..PAGERANK_ITERATIONS {
+        // Participants answering nobody spread their rank over everyone.
+        let dangling: f64 = names
+            .iter()
+            .filter(|name| !out_weights.contains_key(name))
+            .map(|name| ranks[name])
+            .sum();
+        let mut next_ranks: HashMap<String, f64> = names
+            .iter()
+            .map(|name| {
+                let rank = (1.0 - DAMPING) / num_nodes + DAMPING * dangling / num_nodes;
+                (name.clone(), rank)
+            })
+            .collect();
+        for edge in edges {
+            let share = edge.weight() as f64 / out_weights[&edge.source];
+            *next_ranks
+                .get_mut(&edge.target)
+                .expect("Unknown edge target") += DAMPING * ranks[&edge.source] * share;
+        }
+        ranks = next_ranks;
+    }
+
+    return ranks;
+}
+
+/// Interaction graph of chronologically sorted messages. A message answers the previous
+/// one when it comes from someone else within `window_ms`. Neither export keeps which
+/// message is quoted in a reply, so mentions are the only explicit references.
+pub fn get_interaction_graph(
+    messages: &Vec<BaseMessage>,
+    participants: &HashSet<String>,
+    window_ms: i64,
+) -> InteractionGraph {
+    let mut names: Vec<String> = participants
+        .iter()
+        .cloned()
+        .chain(messages.iter().map(|msg| msg.sender_name.clone()))
+        .collect::<HashSet<String>>()
+        .into_iter()
+        .collect();
+    names.sort();
+
+    let mut message_counts: HashMap<&String, i64> = HashMap::new();
+    let mut edges: BTreeMap<(String, String), InteractionEdge> = BTreeMap::new();
+    let mut add_interaction = |source: &String, target: &String, is_reply: bool| {
+        let edge = edges
+            .entry((source.clone(), target.clone()))
+            .or_insert_with(|| InteractionEdge {
+                source: source.clone(),
+                target: target.clone(),
+                replies: 0,
+                mentions: 0,
+            });
+        if is_reply {
+            edge.replies += 1;
+        } else {
+            edge.mentions += 1;
+        }
+    };
+
+    let mut previous: Option<&BaseMessage> = None;
+    for msg in messages {
+        *message_counts.entry(&msg.sender_name).or_insert(0) += 1;
+        if let Some(previous_msg) = previous {
+            if previous_msg.sender_name != msg.sender_name
+                && msg.timestamp_ms - previous_msg.timestamp_ms <= window_ms
+            {
+                add_interaction(&msg.sender_name, &previous_msg.sender_name, true);
+            }
+        }
+        if let Some(content) = &msg.content {
+            for mentioned in get_mentions(content, &names) {
+                if *mentioned != msg.sender_name {
+                    add_interaction(&msg.sender_name, mentioned, false);
+                }
+            }
+        }
+        previous = Some(msg);
+    }
+    let edges: Vec<InteractionEdge> = edges.into_values().collect();
+
+    let pagerank = get_pagerank(&names, &edges);
+    let mut in_strength: HashMap<&String, i64> = HashMap::new();
+    let mut out_strength: HashMap<&String, i64> = HashMap::new();
+    let mut neighbours: HashMap<&String, HashSet<&String>> = HashMap::new();
+    for edge in edges.iter() {
+        *in_strength.entry(&edge.target).or_insert(0) += edge.weight();
+        *out_strength.entry(&edge.source).or_insert(0) += edge.weight();
+        neighbours
+            .entry(&edge.source)
+            .or_default()
+            .insert(&edge.target);
+        neighbours
+            .entry(&edge.target)
+            .or_default()
+            .insert(&edge.source);
+    }
+
+    let nodes = names
+        .iter()
+        .map(|name| GraphNode {
+            name: name.clone(),
+            messages: message_counts.get(name).copied().unwrap_or(0),
+            in_strength: in_strength.get(name).copied().unwrap_or(0),
+            out_strength: out_strength.get(name).copied().unwrap_or(0),
+            degree_centrality: if names.len() > 1 {
+                neighbours.get(name).map_or(0, |x| x.len()) as f64 / (names.len() - 1) as f64
+            } else {
+                0.0
+            },
+            pagerank: pagerank[name],
+        })
+        .collect();
+
+    return InteractionGraph { nodes, edges };
+}
+
+/// Fruchterman–Reingold force layout in the unit square: linked participants attract each
+/// other (more with heavier edges), every pair repels. Starts from a circle so that the
+/// layout is the same at every render.
+pub fn get_force_layout(graph: &InteractionGraph) -> Vec<(f64, f64)> {
+    let num_nodes = graph.nodes.len();
+    if num_nodes == 0 {
+        return vec![];
+    }
+    let ideal_distance = (1.0 / num_nodes as f64).sqrt();
+    let ids: HashMap<&String, usize> = graph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(idx, node)| (&node.name, idx))
+        .collect();
+    let max_weight = graph
+        .edges
+        .iter()
+        .map(|edge| edge.weight())
+        .max()
+        .unwrap_or(1) as f64;
+
+    let mut positions: Vec<(f64, f64)> = (0..num_nodes)
+        .map(|idx| {
+            let angle = 2.0 * std::f64::consts::PI * idx as f64 / num_nodes as f64;
+            (0.5 + 0.4 * angle.cos(), 0.5 + 0.4 * angle.sin())
+        })
+        .collect();
+
+    for iteration in 0..LAYOUT_ITERATIONS {
+        let mut moves = vec![(0.0, 0.0); num_nodes];
+        for i in 0..num_nodes {
+            for j in 0..num_nodes {
+                if i == j {
+                    continue;
+                }
+                let dx = positions[i].0 - positions[j].0;
+                let dy = positions[i].1 - positions[j].1;
+                let distance = (dx * dx + dy * dy).sqrt().max(1e-6);
+                let repulsion = ideal_distance * ideal_distance / distance;
+                moves[i].0 += dx / distance * repulsion;
+                moves[i].1 += dy / distance * repulsion;
+            }
+        }
+        for edge in graph.edges.iter() {
+            let (source, target) = (ids[&edge.source], ids[&edge.target]);
+            let dx = positions[source].0 - positions[target].0;
+            let dy = positions[source].1 - positions[target].1;
+            let distance = (dx * dx + dy * dy).sqrt().max(1e-6);
+            let attraction =
+                distance * distance / ideal_distance * (edge.weight() as f64 / max_weight);
+            moves[source].0 -= dx / distance * attraction;
+            moves[source].1 -= dy / distance * attraction;
+            moves[target].0 += dx / distance * attraction;
+            moves[target].1 += dy / distance * attraction;
+        }
+
+        // The temperature limits the moves and cools down linearly.
+        let temperature = 0.1 * (1.0 - iteration as f64 / LAYOUT_ITERATIONS as f64);
+        for (position, (dx, dy)) in positions.iter_mut().zip(moves) {
+            let length = (dx * dx + dy * dy).sqrt().max(1e-6);
+            let step = length.min(temperature);
+            position.0 = (position.0 + dx / length * step).clamp(0.0, 1.0);
+            position.1 = (position.1 + dy / length * step).clamp(0.0, 1.0);
+        }
+    }
+
+    return positions;
+}
+
+fn escape_xml(text: &str) -> String {
+    return text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+}
+
+fn escape_dot(text: &str) -> String {
+    return text.replace('\\', "\\\\").replace('"', "\\\"");
+}
+
+impl InteractionGraph {
+    /// GraphML document, readable by Gephi, yEd or networkx.
+    pub fn to_graphml(&self) -> String {
+        let mut graphml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"messages\" for=\"node\" attr.name=\"messages\" attr.type=\"long\"/>\n",
+            "  <key id=\"pagerank\" for=\"node\" attr.name=\"pagerank\" attr.type=\"double\"/>\n",
+            "  <key id=\"degree\" for=\"node\" attr.name=\"degree_centrality\" attr.type=\"double\"/>\n",
+            "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"long\"/>\n",
+            "  <key id=\"replies\" for=\"edge\" attr.name=\"replies\" attr.type=\"long\"/>\n",
+            "  <key id=\"mentions\" for=\"edge\" attr.name=\"mentions\" attr.type=\"long\"/>\n",
+            "  <graph id=\"interactions\" edgedefault=\"directed\">\n",
+        ));
+        let ids: HashMap<&String, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| (&node.name, idx))
+            .collect();
+
+        for (idx, node) in self.nodes.iter().enumerate() {
+            graphml.push_str(&format!(
+                concat!(
+                    "    <node id=\"n{}\">\n",
+                    "      <data key=\"label\">{}</data>\n",
+                    "      <data key=\"messages\">{}</data>\n",
+                    "      <data key=\"pagerank\">{}</data>\n",
+                    "      <data key=\"degree\">{}</data>\n",
+                    "    </node>\n",
+                ),
+                idx,
+                escape_xml(&node.name),
+                node.messages,
+                node.pagerank,
+                node.degree_centrality
+            ));
+        }
+        for edge in self.edges.iter() {
+            graphml.push_str(&format!(
+                concat!(
+                    "    <edge source=\"n{}\" target=\"n{}\">\n",
+                    "      <data key=\"weight\">{}</data>\n",
+                    "      <data key=\"replies\">{}</data>\n",
+                    "      <data key=\"mentions\">{}</data>\n",
+                    "    </edge>\n",
+                ),
+                ids[&edge.source],
+                ids[&edge.target],
+                edge.weight(),
+                edge.replies,
+                edge.mentions
+            ));
+        }
+
+        graphml.push_str("  </graph>\n</graphml>\n");
+        return graphml;
+    }
+
+    /// Graphviz DOT document, the edge width follows its weight.
+    pub fn to_dot(&self) -> String {
+        let max_weight = self
+            .edges
+            .iter()
+            .map(|edge| edge.weight())
+            .max()
+            .unwrap_or(1);
+        let mut dot = String::from("digraph interactions {\n");
+
+        for node in self.nodes.iter() {
+            dot.push_str(&format!(
+                "  \"{}\" [messages={}, pagerank={:.4}];\n",
+                escape_dot(&node.name),
+                node.messages,
+                node.pagerank
+            ));
+        }
+        for edge in self.edges.iter() {
+            dot.push_str(&format!(
+                "  \"{}\" -> \"{}\" [weight={}, replies={}, mentions={}, penwidth={:.2}];\n",
+                escape_dot(&edge.source),
+                escape_dot(&edge.target),
+                edge.weight(),
+                edge.replies,
+                edge.mentions,
+                1.0 + 4.0 * edge.weight() as f64 / max_weight as f64
+            ));
+        }
+
+        dot.push_str("}\n");
+        return dot;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_force_layout, get_interaction_graph, get_mentions};
+    use crate::analysis::fixtures::message;
+    use crate::parsers::base::BaseMessage;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_mentions() {
+        let participants = vec![
+            "Anna".to_string(),
+            "Anna Lee".to_string(),
+            "Bob".to_string(),
+        ];
+        let mentions = get_mentions("@anna lee and @Bob, not @Bobby nor bob", &participants);
+        assert_eq!(mentions, vec!["Anna Lee", "Bob"]);
+        assert_eq!(get_mentions("@Anna!", &participants), vec!["Anna"]);
+    }
+
+    #[test]
+    fn test_interaction_graph() {
+        let minute = 60 * 1000;
+        let messages: Vec<BaseMessage> = [
+            ("p1", 0, "hello"),
+            ("p2", minute, "hi"),
+            ("p3", 2 * minute, "hey @p1"),
+            ("p1", 3 * minute, "what's up"),
+            ("p2", 300 * minute, "sorry, late"),
+        ]
+        .iter()
+        .map(|(sender, timestamp_ms, content)| message(sender, *timestamp_ms, Some(content)))
+        .collect();
+        let participants: HashSet<String> = ["p1", "p2", "p3", "p4"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        let graph = get_interaction_graph(&messages, &participants, 60 * minute);
+
+        let edges: Vec<(&str, &str, i64, i64)> = graph
+            .edges
+            .iter()
+            .map(|edge| {
+                (
+                    edge.source.as_str(),
+                    edge.target.as_str(),
+                    edge.replies,
+                    edge.mentions,
+                )
+            })
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("p1", "p3", 1, 0),
+                ("p2", "p1", 1, 0),
+                ("p3", "p1", 0, 1),
+                ("p3", "p2", 1, 0)
+            ]
+        );
+
+        assert_eq!(graph.nodes.len(), 4);
+        let p1 = &graph.nodes[0];
+        assert_eq!(p1.in_strength, 2);
+        assert_eq!(p1.out_strength, 1);
+        assert_eq!(p1.degree_centrality, 2.0 / 3.0);
+        // p4 never wrote: no interaction, lowest rank.
+        let p4 = &graph.nodes[3];
+        assert_eq!(p4.messages, 0);
+        assert_eq!(p4.degree_centrality, 0.0);
+        assert!(graph.nodes.iter().all(|node| node.pagerank >= p4.pagerank));
+        let total_rank: f64 = graph.nodes.iter().map(|node| node.pagerank).sum();
+        assert!((total_rank - 1.0).abs() < 1e-9);
+
+        let graphml = graph.to_graphml();
+        assert_eq!(graphml.matches("<node ").count(), 4);
+        assert!(graphml.contains("<edge source=\"n2\" target=\"n0\">"));
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph interactions {"));
+        assert!(dot.contains("\"p3\" -> \"p1\" [weight=1, replies=0, mentions=1"));
+
+        let positions = get_force_layout(&graph);
+        assert_eq!(positions.len(), 4);
+        assert!(positions
+            .iter()
+            .all(|(x, y)| (0.0..=1.0).contains(x) && (0.0..=1.0).contains(y)));
+        assert_eq!(positions, get_force_layout(&graph));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::parsers::base::BaseMessage;
+use chrono::{DateTime, Datelike, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const WEEK_MS: f64 = 7.0 * 24.0 * 60.0 * 60.0 * 1000.0;
+
+pub const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ActivityHeatmap {
+    /// Average number of messages per week sent each weekday (Monday first) at each hour,
+    /// as `[weekday][hour]`, per participant.
+    pub per_participant: HashMap<String, Vec<Vec<f64>>>,
+    /// Same as `per_participant`, for the whole conversation.
+    pub combined: Vec<Vec<f64>>,
+    /// Number of weeks between the first and the last message (at least one).
+    pub num_weeks: f64,
+}
+
+fn empty_grid() -> Vec<Vec<f64>> {
+    return vec![vec![0.0; 24]; 7];
+}
+
+/// Weekday × hour activity, normalised by the length of the history so that
+/// conversations of different durations can be compared.
+pub fn get_activity_heatmap(messages: &Vec<BaseMessage>) -> ActivityHeatmap {
+    let mut heatmap = ActivityHeatmap {
+        combined: empty_grid(),
+        ..ActivityHeatmap::default()
+    };
+
+    for msg in messages {
+        if let Some(datetime) = DateTime::from_timestamp_millis(msg.timestamp_ms) {
+            let weekday = datetime.weekday().num_days_from_monday() as usize;
+            let hour = datetime.hour() as usize;
+
+            heatmap
+                .per_participant
+                .entry(msg.sender_name.clone())
+                .or_insert_with(empty_grid)[weekday][hour] += 1.0;
+            heatmap.combined[weekday][hour] += 1.0;
+        }
+    }
+
+    let first = messages
+        .iter()
+        .map(|msg| msg.timestamp_ms)
+        .min()
+        .unwrap_or(0);
+    let last = messages
+        .iter()
+        .map(|msg| msg.timestamp_ms)
+        .max()
+        .unwrap_or(0);
+    heatmap.num_weeks = ((last - first) as f64 / WEEK_MS).max(1.0);
+
+    for grid in heatmap
+        .per_participant
+        .values_mut()
+        .chain(std::iter::once(&mut heatmap.combined))
+    {
+        for count in grid.iter_mut().flatten() {
+            *count /= heatmap.num_weeks;
+        }
+    }
+
+    return heatmap;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_activity_heatmap;
+    use crate::analysis::fixtures::{message, timestamp};
+
+    #[test]
+    fn test_activity_heatmap() {
+        let messages = vec![
+            message("p1", timestamp("2024-01-01T20:15:00Z"), None), // Monday evening
+            message("p2", timestamp("2024-01-01T20:45:00Z"), None),
+            message("p1", timestamp("2024-01-06T09:00:00Z"), None), // Saturday morning
+            message("p1", timestamp("2024-01-15T20:15:00Z"), None), // Monday evening, two weeks later
+        ];
+        let heatmap = get_activity_heatmap(&messages);
+
+        assert_eq!(heatmap.num_weeks, 2.0);
+        assert_eq!(heatmap.per_participant["p1"][0][20], 1.0);
+        assert_eq!(heatmap.per_participant["p1"][5][9], 0.5);
+        assert_eq!(heatmap.per_participant["p2"][0][20], 0.5);
+        assert_eq!(heatmap.combined[0][20], 1.5);
+        assert_eq!(heatmap.combined[6].iter().sum::<f64>(), 0.0);
+    }
+}
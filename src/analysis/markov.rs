@@ -0,0 +1,200 @@
+use crate::analysis::random::Rng;
+use crate::parsers::base::BaseMessage;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Number of previous words deciding the next one: 2 keeps some variety, 3 reads better but
+/// mostly quotes the messages of quiet participants.
+pub const DEFAULT_ORDER: usize = 2;
+/// Generation stops there for chains looping on themselves.
+pub const DEFAULT_MAX_WORDS: usize = 40;
+/// Number of tries to get a message which is not a copy of a real one.
+const MAX_ATTEMPTS: usize = 10;
+// Control characters, which never are words of a message.
+const START: &str = "\u{2}";
+const END: &str = "\u{3}";
+
+/// Word level Markov chain of the messages of one participant. Each message is a sentence
+/// going from start tokens to an end token, punctuation and case are kept.
+#[derive(Clone, Debug, Default)]
+pub struct MarkovChain {
+    pub order: usize,
+    /// Words following each state (the `order` previous words) with their count, sorted so
+    /// that a seed always gives the same message.
+    transitions: HashMap<Vec<String>, BTreeMap<String, i64>>,
+    texts: HashSet<String>,
+}
+
+impl MarkovChain {
+    pub fn new(order: usize) -> MarkovChain {
+        return MarkovChain {
+            order: order.max(1),
+            ..MarkovChain::default()
+        };
+    }
+
+    pub fn add_text(&mut self, text: &str) {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        if words.is_empty() {
+            return;
+        }
+        self.texts.insert(words.join(" "));
+
+        let mut state: Vec<String> = vec![START.to_string(); self.order];
+        for word in words.into_iter().chain([END]) {
+            *self
+                .transitions
+                .entry(state.clone())
+                .or_default()
+                .entry(word.to_string())
+                .or_insert(0) += 1;
+            state.remove(0);
+            state.push(word.to_string());
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.transitions.is_empty();
+    }
+
+    fn walk(&self, rng: &mut Rng, max_words: usize) -> String {
+        let mut state: Vec<String> = vec![START.to_string(); self.order];
+        let mut words: Vec<String> = Vec::new();
+
+        while words.len() < max_words {
+            let next = match self.transitions.get(&state) {
+                Some(next) => next,
+                None => break,
+            };
+            let total: i64 = next.values().sum();
+            let mut target = rng.next_f64() * total as f64;
+            let word = next
+                .iter()
+                .find(|(_, count)| {
+                    target -= **count as f64;
+                    target < 0.0
+                })
+                .or_else(|| next.iter().last())
+                .map(|(word, _)| word.clone())
+                .unwrap_or_default();
+            if word == END {
+                break;
+            }
+            state.remove(0);
+            state.push(word.clone());
+            words.push(word);
+        }
+        return words.join(" ");
+    }
+
+    /// A message in the style of the participant, new if possible. `None` when the chain
+    /// learned nothing.
+    pub fn generate(&self, seed: u64, max_words: usize) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut rng = Rng(seed);
+        let mut text = String::new();
+        for _ in 0..MAX_ATTEMPTS {
+            text = self.walk(&mut rng, max_words);
+            if !self.texts.contains(&text) {
+                break;
+            }
+        }
+        return Some(text);
+    }
+}
+
+/// One chain per participant, from the texts they sent.
+pub fn get_markov_chains(
+    messages: &Vec<BaseMessage>,
+    order: usize,
+) -> HashMap<String, MarkovChain> {
+    let mut chains: HashMap<String, MarkovChain> = HashMap::new();
+
+    for msg in messages {
+        if let Some(content) = &msg.content {
+            chains
+                .entry(msg.sender_name.clone())
+                .or_insert_with(|| MarkovChain::new(order))
+                .add_text(content);
+        }
+    }
+    chains.retain(|_, chain| !chain.is_empty());
+    return chains;
+}
+
+/// `num_messages` generated messages per participant, the same ones for the same seed.
+pub fn generate_messages(
+    chains: &HashMap<String, MarkovChain>,
+    seed: u64,
+    num_messages: usize,
+    max_words: usize,
+) -> HashMap<String, Vec<String>> {
+    return chains
+        .iter()
+        .map(|(name, chain)| {
+            let messages: Vec<String> = (0..num_messages as u64)
+                .filter_map(|idx| chain.generate(seed.wrapping_add(idx), max_words))
+                .collect();
+            (name.clone(), messages)
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_messages, get_markov_chains, MarkovChain};
+    use crate::analysis::fixtures::message;
+    use crate::parsers::base::BaseMessage;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_markov_chain() {
+        let mut chain = MarkovChain::new(1);
+        for text in ["the cat sleeps", "the dog runs", "a cat runs"] {
+            chain.add_text(text);
+        }
+        // Every word follows a word it followed in the messages.
+        let sentences: HashSet<&str> = [
+            "the cat sleeps",
+            "the cat runs",
+            "the dog runs",
+            "a cat sleeps",
+            "a cat runs",
+        ]
+        .into();
+        for seed in 0..20 {
+            let text = chain.generate(seed, 40).unwrap();
+            assert!(sentences.contains(text.as_str()), "{}", text);
+        }
+        assert_eq!(chain.generate(7, 40), chain.generate(7, 40));
+        // Never more than the maximum number of words.
+        assert!(chain.generate(3, 2).unwrap().split(' ').count() <= 2);
+        assert!(MarkovChain::new(2).generate(0, 40).is_none());
+    }
+
+    #[test]
+    fn test_generate_messages() {
+        let messages: Vec<BaseMessage> = [
+            ("p1", Some("Hello there, how are you?")),
+            ("p1", Some("Hello there, all good?")),
+            ("p2", None),
+            ("p3", Some("ok")),
+        ]
+        .iter()
+        .map(|(sender, content)| message(sender, 0, *content))
+        .collect();
+
+        let chains = get_markov_chains(&messages, 2);
+        assert!(!chains.contains_key("p2"));
+        assert_eq!(chains["p1"].order, 2);
+
+        let generated = generate_messages(&chains, 42, 3, 40);
+        assert_eq!(generated["p1"].len(), 3);
+        assert!(generated["p1"]
+            .iter()
+            .all(|text| text.starts_with("Hello there,")));
+        // Nothing new can be said with a single word.
+        assert_eq!(generated["p3"], vec!["ok"; 3]);
+    }
+}
@@ -0,0 +1,233 @@
+use crate::analysis::tokenizer::is_url;
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MediaKind {
+    Photo,
+    Video,
+    Gif,
+    Sticker,
+    /// Voice notes and audio files.
+    Audio,
+    Link,
+    /// Documents and media of unknown type (WhatsApp's `<Media omitted>`).
+    Other,
+}
+
+impl MediaKind {
+    pub const ALL: [MediaKind; 7] = [
+        MediaKind::Photo,
+        MediaKind::Video,
+        MediaKind::Gif,
+        MediaKind::Sticker,
+        MediaKind::Audio,
+        MediaKind::Link,
+        MediaKind::Other,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            MediaKind::Photo => "photo",
+            MediaKind::Video => "video",
+            MediaKind::Gif => "gif",
+            MediaKind::Sticker => "sticker",
+            MediaKind::Audio => "audio",
+            MediaKind::Link => "link",
+            MediaKind::Other => "other",
+        };
+    }
+}
+
+/// One photo, video, link... shared in a message. A message can share several of them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SharedMedia {
+    pub sender_name: String,
+    pub timestamp_ms: i64,
+    pub kind: MediaKind,
+    /// Address of links.
+    pub url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MediaStats {
+    /// Number of items of each kind shared by each participant, {participant: {kind: count}}.
+    pub counts: HashMap<String, BTreeMap<String, i64>>,
+    /// Part of all the items of each kind shared by each participant, {participant: {kind: share}}.
+    pub shares: HashMap<String, BTreeMap<String, f64>>,
+    /// Number of items of each kind shared each month (%Y-%m), {kind: {month: count}}.
+    pub over_time: BTreeMap<String, BTreeMap<String, i64>>,
+    /// Most shared website domains with their count, most shared first.
+    pub top_domains: Vec<(String, i64)>,
+}
+
+/// The URLs of a text, without the punctuation that may follow them.
+pub fn get_links(text: &str) -> Vec<String> {
+    return text
+        .split_whitespace()
+        .filter(|chunk| is_url(chunk))
+        .map(|chunk| {
+            chunk
+                .trim_end_matches(|c: char| c.is_ascii_punctuation() && c != '/')
+                .to_string()
+        })
+        .collect();
+}
+
+/// Host of an URL without its "www." prefix: "https://www.example.com/a?b" => "example.com".
+pub fn get_domain(url: &str) -> Option<String> {
+    let url = url.to_lowercase();
+    let without_scheme = match url.find("://") {
+        Some(idx) => &url[idx + 3..],
+        None => url.as_str(),
+    };
+    let host = without_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let host = host.rsplit('@').next().unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default();
+    let host = host.strip_prefix("www.").unwrap_or(host);
+
+    if !host.contains('.') {
+        return None;
+    }
+    return Some(host.to_string());
+}
+
+/// Guess the kind of an attached file from its name, following the naming of WhatsApp
+/// exports ("IMG-20240101-WA0001.jpg", "PTT-20240101-WA0002.opus", "STK-...webp").
+pub fn get_attachment_kind(file_name: &str) -> MediaKind {
+    let file_name = file_name.to_lowercase();
+    let extension = file_name.rsplit('.').next().unwrap_or_default();
+
+    if file_name.starts_with("stk-") || file_name.contains("sticker") {
+        return MediaKind::Sticker;
+    }
+    if extension == "gif" || file_name.contains("-gif-") {
+        return MediaKind::Gif;
+    }
+    return match extension {
+        "jpg" | "jpeg" | "png" | "webp" | "heic" => MediaKind::Photo,
+        "mp4" | "mov" | "3gp" | "mkv" | "webm" => MediaKind::Video,
+        "opus" | "ogg" | "m4a" | "mp3" | "aac" | "wav" => MediaKind::Audio,
+        _ => MediaKind::Other,
+    };
+}
+
+pub fn get_media_stats(media: &Vec<SharedMedia>, num_domains: usize) -> MediaStats {
+    let mut stats = MediaStats::default();
+    let mut totals: HashMap<MediaKind, i64> = HashMap::new();
+    let mut domains: HashMap<String, i64> = HashMap::new();
+
+    for item in media {
+        *stats
+            .counts
+            .entry(item.sender_name.clone())
+            .or_default()
+            .entry(item.kind.name().to_string())
+            .or_insert(0) += 1;
+        *totals.entry(item.kind).or_insert(0) += 1;
+
+        if let Some(datetime) = DateTime::from_timestamp_millis(item.timestamp_ms) {
+            *stats
+                .over_time
+                .entry(item.kind.name().to_string())
+                .or_default()
+                .entry(datetime.format("%Y-%m").to_string())
+                .or_insert(0) += 1;
+        }
+        if let Some(domain) = item.url.as_deref().and_then(get_domain) {
+            *domains.entry(domain).or_insert(0) += 1;
+        }
+    }
+
+    for (name, counts) in stats.counts.iter() {
+        let shares = MediaKind::ALL
+            .iter()
+            .filter_map(|kind| {
+                let count = counts.get(kind.name())?;
+                Some((kind.name().to_string(), *count as f64 / totals[kind] as f64))
+            })
+            .collect();
+        stats.shares.insert(name.clone(), shares);
+    }
+
+    let mut top_domains: Vec<(String, i64)> = domains.into_iter().collect();
+    top_domains.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top_domains.truncate(num_domains);
+    stats.top_domains = top_domains;
+
+    return stats;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_attachment_kind, get_domain, get_links, get_media_stats, MediaKind, SharedMedia,
+    };
+
+    #[test]
+    fn test_links() {
+        assert_eq!(
+            get_links("look https://www.Example.com/a?b=1, and www.test.fr."),
+            vec!["https://www.Example.com/a?b=1", "www.test.fr"]
+        );
+        assert_eq!(
+            get_domain("https://www.Example.com/a?b=1"),
+            Some("example.com".to_string())
+        );
+        assert_eq!(
+            get_domain("http://user@music.site.org:8080"),
+            Some("music.site.org".to_string())
+        );
+        assert_eq!(get_domain("https://localhost/"), None);
+
+        assert_eq!(
+            get_attachment_kind("IMG-20240101-WA0001.jpg"),
+            MediaKind::Photo
+        );
+        assert_eq!(
+            get_attachment_kind("STK-20240101-WA0003.webp"),
+            MediaKind::Sticker
+        );
+        assert_eq!(
+            get_attachment_kind("PTT-20240101-WA0002.opus"),
+            MediaKind::Audio
+        );
+        assert_eq!(get_attachment_kind("report.pdf"), MediaKind::Other);
+    }
+
+    #[test]
+    fn test_media_stats() {
+        let january = 1704067200000;
+        let february = 1706745600000;
+        let media: Vec<SharedMedia> = [
+            ("p1", january, MediaKind::Photo, None),
+            ("p1", january, MediaKind::Photo, None),
+            ("p2", february, MediaKind::Photo, None),
+            ("p2", february, MediaKind::Link, Some("https://a.com/x")),
+            ("p1", february, MediaKind::Link, Some("https://www.a.com/y")),
+            ("p1", february, MediaKind::Link, Some("https://b.com")),
+        ]
+        .iter()
+        .map(|(sender, timestamp_ms, kind, url)| SharedMedia {
+            sender_name: sender.to_string(),
+            timestamp_ms: *timestamp_ms,
+            kind: *kind,
+            url: url.map(|url| url.to_string()),
+        })
+        .collect();
+
+        let stats = get_media_stats(&media, 1);
+
+        assert_eq!(stats.counts["p1"]["photo"], 2);
+        assert_eq!(stats.counts["p1"]["link"], 2);
+        assert!(!stats.counts["p2"].contains_key("video"));
+        assert_eq!(stats.shares["p2"]["photo"], 1.0 / 3.0);
+        assert_eq!(stats.over_time["photo"]["2024-01"], 2);
+        assert_eq!(stats.over_time["photo"]["2024-02"], 1);
+        assert_eq!(stats.top_domains, vec![("a.com".to_string(), 2)]);
+    }
+}
@@ -0,0 +1,397 @@
+use crate::analysis::sessions::get_sessions;
+use crate::parsers::base::BaseMessage;
+use chrono::{DateTime, Datelike, NaiveDate};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Message counts worth celebrating.
+const MESSAGE_COUNTS: [usize; 3] = [1_000, 10_000, 100_000];
+// cSpell: disable
+const BIRTHDAY_PATTERN: &str = r"(?i)\b(?:happy\s+(?:birthday|b-?day)|joyeux\s+anniversaire|bon\s+anniversaire|feliz\s+cumplea[nñ]os|alles\s+gute\s+zum\s+geburtstag)\b";
+// cSpell: enable
+/// A day with this many times the usual number of messages of its year is a spike.
+const SPIKE_FACTOR: f64 = 3.0;
+/// A date must stand out in this many years to be recurring.
+const MIN_RECURRING_YEARS: usize = 2;
+const VARIATION_SELECTOR_EMOJI: char = '\u{FE0F}';
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum MilestoneKind {
+    FirstMessage,
+    MessageCount,
+    LongestSession,
+    BusiestDay,
+    LongestSilence,
+    FirstEmoji,
+    /// Birthday wishes or an activity spike on the same day of several years.
+    RecurringDate,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Milestone {
+    pub kind: MilestoneKind,
+    pub timestamp_ms: i64,
+    /// What happened, "10000th message", "Longest silence: 12 days"...
+    pub title: String,
+    /// The message marking the milestone.
+    pub sender_name: String,
+    pub content: Option<String>,
+}
+
+impl Milestone {
+    fn new(kind: MilestoneKind, title: String, msg: &BaseMessage) -> Milestone {
+        return Milestone {
+            kind,
+            timestamp_ms: msg.timestamp_ms,
+            title,
+            sender_name: msg.sender_name.clone(),
+            content: msg.content.clone(),
+        };
+    }
+}
+
+fn format_duration(duration_ms: i64) -> String {
+    let minutes = duration_ms / 60_000;
+    return match minutes {
+        minutes if minutes < 60 => format!("{} min", minutes),
+        minutes if minutes < 48 * 60 => format!("{}h{:02}", minutes / 60, minutes % 60),
+        minutes => format!("{} days", minutes / (24 * 60)),
+    };
+}
+
+fn get_date(msg: &BaseMessage) -> Option<NaiveDate> {
+    return DateTime::from_timestamp_millis(msg.timestamp_ms).map(|datetime| datetime.date_naive());
+}
+
+/// Month and day, the same every year.
+fn format_day(date: NaiveDate) -> String {
+    return date.format("%-d %B").to_string();
+}
+
+/// Activity of one day.
+struct DayActivity {
+    messages: i64,
+    wishes: i64,
+    /// Index of the first message of the day.
+    first_idx: usize,
+    /// Index of the first birthday wish of the day.
+    first_wish_idx: Option<usize>,
+}
+
+/// Days (month, day) on which something happens every year: birthday wishes ("happy
+/// birthday", "joyeux anniversaire"...) or many more messages than usual, in several years.
+fn get_recurring_dates(messages: &Vec<BaseMessage>) -> Vec<Milestone> {
+    let birthday = Regex::new(BIRTHDAY_PATTERN).expect("Invalid birthday pattern");
+    // {(month, day): {year: activity}}
+    let mut days: BTreeMap<(u32, u32), BTreeMap<i32, DayActivity>> = BTreeMap::new();
+    let mut year_counts: HashMap<i32, (i64, BTreeSet<(u32, u32)>)> = HashMap::new();
+
+    for (idx, msg) in messages.iter().enumerate() {
+        let date = match get_date(msg) {
+            Some(date) => date,
+            None => continue,
+        };
+        let is_wish = msg
+            .content
+            .as_deref()
+            .is_some_and(|content| birthday.is_match(content));
+        let day = days
+            .entry((date.month(), date.day()))
+            .or_default()
+            .entry(date.year())
+            .or_insert(DayActivity {
+                messages: 0,
+                wishes: 0,
+                first_idx: idx,
+                first_wish_idx: None,
+            });
+        day.messages += 1;
+        if is_wish {
+            day.wishes += 1;
+            day.first_wish_idx = day.first_wish_idx.or(Some(idx));
+        }
+        let year = year_counts.entry(date.year()).or_default();
+        year.0 += 1;
+        year.1.insert((date.month(), date.day()));
+    }
+
+    let mut milestones: Vec<Milestone> = Vec::new();
+    for years in days.values() {
+        let first_idx = years.values().map(|day| day.first_idx).min().unwrap_or(0);
+        let date = get_date(&messages[first_idx]).expect("Dated message");
+        let wishes: i64 = years.values().map(|day| day.wishes).sum();
+        let wished_years = years.values().filter(|day| day.wishes > 0).count();
+
+        if wished_years >= MIN_RECURRING_YEARS {
+            let first_wish = years
+                .values()
+                .filter_map(|day| day.first_wish_idx)
+                .min()
+                .unwrap_or(first_idx);
+            milestones.push(Milestone::new(
+                MilestoneKind::RecurringDate,
+                format!("Birthday on {}? {} wishes", format_day(date), wishes),
+                &messages[first_wish],
+            ));
+            continue;
+        }
+
+        let spike_years = years
+            .iter()
+            .filter(|(year, day)| {
+                let (year_total, year_days) = &year_counts[year];
+                let usual = *year_total as f64 / year_days.len() as f64;
+                day.messages as f64 >= SPIKE_FACTOR * usual && year_days.len() > 1
+            })
+            .count();
+        if spike_years >= MIN_RECURRING_YEARS {
+            milestones.push(Milestone::new(
+                MilestoneKind::RecurringDate,
+                format!(
+                    "Busy every year on {}: {} years out of {}",
+                    format_day(date),
+                    spike_years,
+                    years.len()
+                ),
+                &messages[first_idx],
+            ));
+        }
+    }
+    return milestones;
+}
+
+/// Milestones of chronologically sorted messages, in chronological order. `emojis` are
+/// the emojis whose first use is a milestone.
+pub fn get_milestones(
+    messages: &Vec<BaseMessage>,
+    emojis: &[String],
+    gap_ms: i64,
+) -> Vec<Milestone> {
+    let mut milestones: Vec<Milestone> = Vec::new();
+    let first = match messages.first() {
+        Some(first) => first,
+        None => return milestones,
+    };
+    milestones.push(Milestone::new(
+        MilestoneKind::FirstMessage,
+        "First message".to_string(),
+        first,
+    ));
+
+    for count in MESSAGE_COUNTS {
+        if let Some(msg) = messages.get(count - 1) {
+            milestones.push(Milestone::new(
+                MilestoneKind::MessageCount,
+                format!("{}th message", count),
+                msg,
+            ));
+        }
+    }
+
+    if let Some(session) = get_sessions(messages, gap_ms)
+        .into_iter()
+        .max_by_key(|session| {
+            (
+                messages[session.end - 1].timestamp_ms - messages[session.start].timestamp_ms,
+                session.len(),
+            )
+        })
+    {
+        let duration_ms =
+            messages[session.end - 1].timestamp_ms - messages[session.start].timestamp_ms;
+        milestones.push(Milestone::new(
+            MilestoneKind::LongestSession,
+            format!(
+                "Longest session: {} messages over {}",
+                session.len(),
+                format_duration(duration_ms)
+            ),
+            &messages[session.start],
+        ));
+    }
+
+    let mut days: BTreeMap<NaiveDate, (i64, usize)> = BTreeMap::new();
+    for (idx, msg) in messages.iter().enumerate() {
+        if let Some(date) = get_date(msg) {
+            days.entry(date).or_insert((0, idx)).0 += 1;
+        }
+    }
+    // The earliest of the busiest days.
+    if let Some((date, (count, idx))) = days
+        .iter()
+        .max_by(|a, b| a.1 .0.cmp(&b.1 .0).then_with(|| b.0.cmp(a.0)))
+    {
+        milestones.push(Milestone::new(
+            MilestoneKind::BusiestDay,
+            format!(
+                "Busiest day: {} messages on {}",
+                count,
+                date.format("%Y-%m-%d")
+            ),
+            &messages[*idx],
+        ));
+    }
+
+    if let Some(idx) = (1..messages.len()).max_by_key(|idx| {
+        (
+            messages[*idx].timestamp_ms - messages[idx - 1].timestamp_ms,
+            std::cmp::Reverse(*idx),
+        )
+    }) {
+        let duration_ms = messages[idx].timestamp_ms - messages[idx - 1].timestamp_ms;
+        milestones.push(Milestone::new(
+            MilestoneKind::LongestSilence,
+            format!(
+                "Longest silence: {}, broken by {}",
+                format_duration(duration_ms),
+                messages[idx].sender_name
+            ),
+            &messages[idx],
+        ));
+    }
+
+    for emoji in emojis {
+        let emoji = emoji.replace(VARIATION_SELECTOR_EMOJI, "");
+        let first_use = messages.iter().find(|msg| {
+            msg.content.as_deref().is_some_and(|content| {
+                // Graphemes rather than `get_emojis`, which skips text symbols like a bare ❤.
+                content
+                    .graphemes(true)
+                    .any(|used| used.replace(VARIATION_SELECTOR_EMOJI, "") == emoji)
+            })
+        });
+        if let Some(msg) = first_use {
+            milestones.push(Milestone::new(
+                MilestoneKind::FirstEmoji,
+                format!("First {}", emoji),
+                msg,
+            ));
+        }
+    }
+
+    milestones.extend(get_recurring_dates(messages));
+    milestones.sort_by_key(|milestone| milestone.timestamp_ms);
+    return milestones;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_milestones, MilestoneKind};
+    use crate::analysis::fixtures::{message, timestamp};
+    use crate::parsers::base::BaseMessage;
+
+    #[test]
+    fn test_milestones() {
+        let mut messages = vec![
+            message("p1", timestamp("2021-01-10T10:00:00Z"), Some("hi")),
+            message("p2", timestamp("2021-01-10T10:05:00Z"), Some("hello ❤")),
+            message(
+                "p1",
+                timestamp("2021-03-04T09:00:00Z"),
+                Some("Happy birthday!!"),
+            ),
+            message("p2", timestamp("2021-03-04T09:01:00Z"), Some("thanks ❤️")),
+        ];
+        // A long evening chat.
+        for minute in 0..30 {
+            messages.push(message(
+                if minute % 2 == 0 { "p1" } else { "p2" },
+                timestamp(&format!("2021-06-01T20:{:02}:00Z", minute * 2)),
+                Some("chatting"),
+            ));
+        }
+        messages.push(message(
+            "p2",
+            timestamp("2022-03-04T08:00:00Z"),
+            Some("Joyeux anniversaire 🎂"),
+        ));
+        messages.push(message(
+            "p1",
+            timestamp("2022-03-05T08:00:00Z"),
+            Some("merci"),
+        ));
+
+        let milestones = get_milestones(&messages, &["❤️".to_string()], 30 * 60 * 1000);
+
+        let kinds: Vec<&MilestoneKind> =
+            milestones.iter().map(|milestone| &milestone.kind).collect();
+        assert_eq!(kinds[0], &MilestoneKind::FirstMessage);
+        // Too few messages for the 1000th.
+        assert!(!kinds.contains(&&MilestoneKind::MessageCount));
+        let find = |kind: MilestoneKind| {
+            milestones
+                .iter()
+                .find(|milestone| milestone.kind == kind)
+                .unwrap()
+        };
+
+        // The heart without variation selector is the same emoji.
+        assert_eq!(
+            find(MilestoneKind::FirstEmoji).content.as_deref(),
+            Some("hello ❤")
+        );
+        assert_eq!(
+            find(MilestoneKind::LongestSession).title,
+            "Longest session: 30 messages over 58 min"
+        );
+        assert_eq!(
+            find(MilestoneKind::BusiestDay).title,
+            "Busiest day: 30 messages on 2021-06-01"
+        );
+        let silence = find(MilestoneKind::LongestSilence);
+        assert_eq!(silence.title, "Longest silence: 275 days, broken by p2");
+        assert_eq!(silence.content.as_deref(), Some("Joyeux anniversaire 🎂"));
+
+        let birthday = find(MilestoneKind::RecurringDate);
+        assert_eq!(birthday.title, "Birthday on 4 March? 2 wishes");
+        assert_eq!(birthday.content.as_deref(), Some("Happy birthday!!"));
+        // Chronological order.
+        assert!(milestones
+            .windows(2)
+            .all(|pair| pair[0].timestamp_ms <= pair[1].timestamp_ms));
+    }
+
+    #[test]
+    fn test_recurring_activity() {
+        let mut messages: Vec<BaseMessage> = Vec::new();
+        for year in [2021, 2022] {
+            for month in 3..8 {
+                messages.push(message(
+                    "p1",
+                    timestamp(&format!("{}-{:02}-01T10:00:00Z", year, month)),
+                    Some("hey"),
+                ));
+            }
+            for minute in 0..10 {
+                messages.push(message(
+                    "p2",
+                    timestamp(&format!("{}-02-14T20:{:02}:00Z", year, minute)),
+                    Some("love you"),
+                ));
+            }
+        }
+        // Wished twice, but a single year is no recurring birthday.
+        for minute in 0..2 {
+            messages.push(message(
+                "p1",
+                timestamp(&format!("2021-09-09T10:{:02}:00Z", minute)),
+                Some("happy birthday to your mom"),
+            ));
+        }
+        messages.sort_by_key(|msg| msg.timestamp_ms);
+
+        let milestones = get_milestones(&messages, &[], 30 * 60 * 1000);
+
+        let recurring: Vec<&str> = milestones
+            .iter()
+            .filter(|milestone| milestone.kind == MilestoneKind::RecurringDate)
+            .map(|milestone| milestone.title.as_str())
+            .collect();
+        assert_eq!(
+            recurring,
+            vec!["Busy every year on 14 February: 2 years out of 2"]
+        );
+    }
+}
@@ -0,0 +1,56 @@
+pub mod attribution;
+pub mod distinctive;
+pub mod emojis;
+pub mod filter;
+pub mod graph;
+pub mod heatmap;
+pub mod markov;
+pub mod media;
+pub mod milestones;
+pub mod ngrams;
+pub mod random;
+pub mod reacted;
+pub mod report;
+pub mod sentiment;
+pub mod sessions;
+pub mod stats;
+pub mod stopwords;
+pub mod style;
+pub mod summary;
+pub mod survival;
+pub mod timeline;
+pub mod tokenizer;
+pub mod topics;
+pub mod tracking;
+pub mod turns;
+pub mod unanswered;
+
+/// Messages built by the tests of the analyses.
+#[cfg(test)]
+pub mod fixtures {
+    use crate::parsers::base::BaseMessage;
+
+    /// Milliseconds since the epoch of an RFC 3339 date ("2024-01-01T20:15:00Z").
+    pub fn timestamp(date: &str) -> i64 {
+        return chrono::DateTime::parse_from_rfc3339(date)
+            .unwrap()
+            .timestamp_millis();
+    }
+
+    /// A message without text (photo, sticker...) when `content` is `None`.
+    pub fn message(sender: &str, timestamp_ms: i64, content: Option<&str>) -> BaseMessage {
+        return BaseMessage {
+            sender_name: sender.to_string(),
+            timestamp_ms,
+            content: content.map(|text| text.to_string()),
+        };
+    }
+
+    /// Texts of `(sender, content)`, all sent at the same time.
+    pub fn messages_from(contents: &[(&str, &str)]) -> Vec<BaseMessage> {
+        return contents
+            .iter()
+            .map(|(sender, content)| message(sender, 0, Some(content)))
+            .collect();
+    }
+}
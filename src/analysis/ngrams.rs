@@ -0,0 +1,306 @@
+use crate::analysis::distinctive::get_term_counts;
+use crate::analysis::stopwords::StopWords;
+use crate::analysis::tokenizer::{tokenize, TokenizerOptions};
+use crate::parsers::base::BaseMessage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Log-likelihood ratio above which a collocation is significant (chi² with 1 degree
+/// of freedom, p < 0.001).
+const SIGNIFICANT_LOG_LIKELIHOOD: f64 = 10.83;
+/// Minimum number of occurrences of a bigram to be considered as a collocation.
+const MIN_COLLOCATION_COUNT: f64 = 3.0;
+/// Longest message (in words) that can be a catchphrase.
+const MAX_CATCHPHRASE_WORDS: usize = 6;
+/// Minimum number of times a message must be repeated to be a catchphrase.
+const MIN_CATCHPHRASE_COUNT: i64 = 3;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct NgramCounts {
+    /// Most frequent n-grams of the whole conversation.
+    pub overall: Vec<(String, i64)>,
+    /// Most frequent n-grams of each participant.
+    pub per_participant: HashMap<String, Vec<(String, i64)>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Collocation {
+    pub bigram: String,
+    pub count: i64,
+    /// Pointwise mutual information (log2).
+    pub pmi: f64,
+    /// Dunning log-likelihood ratio (G²).
+    pub log_likelihood: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Collocations {
+    pub overall: Vec<Collocation>,
+    pub per_participant: HashMap<String, Vec<Collocation>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PhraseStats {
+    pub bigrams: NgramCounts,
+    pub trigrams: NgramCounts,
+    pub collocations: Collocations,
+    /// Short messages each participant sends over and over, with their count.
+    pub catchphrases: HashMap<String, Vec<(String, i64)>>,
+}
+
+fn top_counts(counts: HashMap<String, f64>, num_phrases: usize) -> Vec<(String, i64)> {
+    let mut counts: Vec<(String, i64)> = counts
+        .into_iter()
+        .map(|(term, count)| (term, count as i64))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(num_phrases);
+    return counts;
+}
+
+pub fn get_frequent_ngrams(
+    messages: &Vec<BaseMessage>,
+    stop_words: &StopWords,
+    ngram_size: usize,
+    num_phrases: usize,
+) -> NgramCounts {
+    let term_counts = get_term_counts(messages, stop_words, ngram_size);
+
+    let mut overall: HashMap<String, f64> = HashMap::new();
+    let mut ngrams = NgramCounts::default();
+
+    for (name, counts) in term_counts.into_iter() {
+        for (term, count) in counts.iter() {
+            *overall.entry(term.clone()).or_insert(0.0) += count;
+        }
+        ngrams
+            .per_participant
+            .insert(name, top_counts(counts, num_phrases));
+    }
+    ngrams.overall = top_counts(overall, num_phrases);
+
+    return ngrams;
+}
+
+fn x_log_x_over_y(x: f64, y: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    return x * (x / y).ln();
+}
+
+/// Significant collocations among bigram counts, best (highest log-likelihood) first.
+fn score_collocations(bigrams: &HashMap<String, f64>, num_phrases: usize) -> Vec<Collocation> {
+    let total: f64 = bigrams.values().sum();
+    let mut first_counts: HashMap<&str, f64> = HashMap::new();
+    let mut second_counts: HashMap<&str, f64> = HashMap::new();
+    for (bigram, count) in bigrams.iter() {
+        if let Some((first, second)) = bigram.split_once(' ') {
+            *first_counts.entry(first).or_insert(0.0) += count;
+            *second_counts.entry(second).or_insert(0.0) += count;
+        }
+    }
+
+    let mut collocations: Vec<Collocation> = bigrams
+        .iter()
+        .filter(|(_, count)| **count >= MIN_COLLOCATION_COUNT)
+        .filter_map(|(bigram, count)| {
+            let (first, second) = bigram.split_once(' ')?;
+            let first_count = first_counts[first];
+            let second_count = second_counts[second];
+
+            // 2x2 contingency table: observed counts and their expectation under independence.
+            let observed = [
+                *count,
+                first_count - count,
+                second_count - count,
+                total - first_count - second_count + count,
+            ];
+            let expected = [
+                first_count * second_count / total,
+                first_count * (total - second_count) / total,
+                (total - first_count) * second_count / total,
+                (total - first_count) * (total - second_count) / total,
+            ];
+            let log_likelihood: f64 = 2.0
+                * observed
+                    .iter()
+                    .zip(expected.iter())
+                    .map(|(o, e)| x_log_x_over_y(*o, *e))
+                    .sum::<f64>();
+            let pmi = (count * total / (first_count * second_count)).log2();
+
+            // Only keep pairs seen more often than chance.
+            if pmi <= 0.0 || log_likelihood < SIGNIFICANT_LOG_LIKELIHOOD {
+                return None;
+            }
+
+            return Some(Collocation {
+                bigram: bigram.clone(),
+                count: *count as i64,
+                pmi,
+                log_likelihood,
+            });
+        })
+        .collect();
+
+    collocations.sort_by(|a, b| {
+        b.log_likelihood
+            .total_cmp(&a.log_likelihood)
+            .then_with(|| a.bigram.cmp(&b.bigram))
+    });
+    collocations.truncate(num_phrases);
+
+    return collocations;
+}
+
+pub fn get_collocations(
+    messages: &Vec<BaseMessage>,
+    stop_words: &StopWords,
+    num_phrases: usize,
+) -> Collocations {
+    let term_counts = get_term_counts(messages, stop_words, 2);
+
+    let mut overall: HashMap<String, f64> = HashMap::new();
+    let mut collocations = Collocations::default();
+
+    for (name, counts) in term_counts.iter() {
+        for (term, count) in counts.iter() {
+            *overall.entry(term.clone()).or_insert(0.0) += count;
+        }
+        collocations
+            .per_participant
+            .insert(name.clone(), score_collocations(counts, num_phrases));
+    }
+    collocations.overall = score_collocations(&overall, num_phrases);
+
+    return collocations;
+}
+
+/// Short messages (normalised: case, punctuation and emojis are ignored) that a participant
+/// sends again and again. Messages made only of stop words ("ok", "haha") are skipped.
+pub fn get_catchphrases(
+    messages: &Vec<BaseMessage>,
+    stop_words: &StopWords,
+    num_phrases: usize,
+) -> HashMap<String, Vec<(String, i64)>> {
+    let mut phrase_counts: HashMap<String, HashMap<String, f64>> = HashMap::new();
+    let tokenizer_options = TokenizerOptions {
+        split_elisions: false,
+        keep_numbers: true,
+        ..TokenizerOptions::default()
+    };
+
+    for msg in messages {
+        if let Some(content) = &msg.content {
+            let tokens = tokenize(content, &tokenizer_options);
+            if tokens.is_empty()
+                || tokens.len() > MAX_CATCHPHRASE_WORDS
+                || tokens.iter().all(|token| stop_words.contains(token))
+            {
+                continue;
+            }
+
+            *phrase_counts
+                .entry(msg.sender_name.clone())
+                .or_default()
+                .entry(tokens.join(" "))
+                .or_insert(0.0) += 1.0;
+        }
+    }
+
+    return phrase_counts
+        .into_iter()
+        .map(|(name, counts)| {
+            let mut catchphrases = top_counts(counts, usize::MAX);
+            catchphrases.retain(|(_, count)| *count >= MIN_CATCHPHRASE_COUNT);
+            catchphrases.truncate(num_phrases);
+            (name, catchphrases)
+        })
+        .collect();
+}
+
+pub fn get_phrase_stats(
+    messages: &Vec<BaseMessage>,
+    stop_words: &StopWords,
+    num_phrases: usize,
+) -> PhraseStats {
+    return PhraseStats {
+        bigrams: get_frequent_ngrams(messages, stop_words, 2, num_phrases),
+        trigrams: get_frequent_ngrams(messages, stop_words, 3, num_phrases),
+        collocations: get_collocations(messages, stop_words, num_phrases),
+        catchphrases: get_catchphrases(messages, stop_words, num_phrases),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_catchphrases, get_collocations, get_frequent_ngrams};
+    use crate::analysis::fixtures::messages_from;
+    use crate::analysis::stopwords::{Language, StopWords};
+
+    #[test]
+    fn test_frequent_trigrams() {
+        let messages = messages_from(&[
+            ("p1", "On se voit demain ?"),
+            ("p2", "Oui on se voit demain"),
+            ("p1", "Super, on se voit demain soir alors"),
+        ]);
+        let stop_words = StopWords::new(&[Language::French]);
+        let trigrams = get_frequent_ngrams(&messages, &stop_words, 3, 2);
+
+        assert_eq!(trigrams.overall[0], ("on se voit".to_string(), 3));
+        assert_eq!(
+            trigrams.per_participant["p1"][0],
+            ("on se voit".to_string(), 2)
+        );
+        assert_eq!(
+            trigrams.per_participant["p2"][0],
+            ("on se voit".to_string(), 1)
+        );
+    }
+
+    #[test]
+    fn test_collocations() {
+        let mut contents = vec![("p1", "new york is big"); 5];
+        contents.extend(vec![("p2", "the city is big and new"); 3]);
+        contents.extend(vec![("p2", "york has a cathedral"); 2]);
+        contents.extend(vec![("p1", "pizza is good, pasta is good too"); 4]);
+        let messages = messages_from(&contents);
+        let stop_words = StopWords::new(&[Language::English]);
+
+        let collocations = get_collocations(&messages, &stop_words, 5);
+        let bigrams: Vec<&str> = collocations
+            .overall
+            .iter()
+            .map(|c| c.bigram.as_str())
+            .collect();
+        assert!(bigrams.contains(&"new york"));
+        assert!(collocations.overall.iter().all(|c| c.pmi > 0.0));
+        assert!(collocations.per_participant["p1"]
+            .iter()
+            .any(|c| c.bigram == "new york" && c.count == 5));
+    }
+
+    #[test]
+    fn test_catchphrases() {
+        let messages = messages_from(&[
+            ("p1", "Love you too!"),
+            ("p1", "love you too ❤️"),
+            ("p1", "Love you too"),
+            ("p1", "ok"),
+            ("p1", "ok"),
+            ("p1", "ok"),
+            ("p2", "On se voit demain"),
+            (
+                "p2",
+                "this is a much longer message that is not a catchphrase",
+            ),
+        ]);
+        let stop_words = StopWords::new(&[Language::English]);
+        let catchphrases = get_catchphrases(&messages, &stop_words, 5);
+
+        assert_eq!(catchphrases["p1"], vec![("love you too".to_string(), 3)]);
+        assert!(catchphrases["p2"].is_empty());
+    }
+}
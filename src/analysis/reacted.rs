@@ -0,0 +1,282 @@
+use crate::analysis::media::get_links;
+use crate::analysis::stopwords::StopWords;
+use crate::analysis::tokenizer::{tokenize, TokenizerOptions};
+use chrono::{DateTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Words found in less messages are too rare for their reaction rate to mean anything.
+pub const DEFAULT_MIN_WORD_MESSAGES: i64 = 5;
+/// Upper bounds (in characters, exclusive) of the length buckets, the last bucket is open.
+const LENGTH_BOUNDS: [usize; 5] = [10, 50, 100, 200, 500];
+/// Weight (in messages) of the overall rate when smoothing the rate of a word, so that a
+/// word seen in 5 reacted messages does not beat one seen in 100 mostly reacted ones.
+const PRIOR_WEIGHT: f64 = 10.0;
+
+/// A message with the people who reacted to it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReactedMessage {
+    pub sender_name: String,
+    pub timestamp_ms: i64,
+    pub content: Option<String>,
+    /// Photos, videos, stickers... attached to the message.
+    pub has_media: bool,
+    /// Who reacted, reactions to one's own messages excluded.
+    pub actors: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReactionRate {
+    pub label: String,
+    pub messages: i64,
+    /// Messages with at least one reaction.
+    pub reacted: i64,
+    pub rate: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WordReactions {
+    pub word: String,
+    /// Messages containing the word.
+    pub messages: i64,
+    pub rate: f64,
+    /// Smoothed rate of the word divided by the rate of all the messages.
+    pub lift: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ReactedToStats {
+    /// Share of all the messages with at least one reaction.
+    pub overall_rate: f64,
+    /// Rate of the texts by length ("<10", "10-49"... characters).
+    pub by_length: Vec<ReactionRate>,
+    /// Rate by hour of the day (UTC), one entry per hour.
+    pub by_hour: Vec<ReactionRate>,
+    /// Rate of the plain texts, texts with a link and media.
+    pub by_content: Vec<ReactionRate>,
+    /// Messages with the most reactions, most reacted first.
+    pub top_messages: Vec<ReactedMessage>,
+    /// Words whose messages get the most reactions, highest lift first.
+    pub top_words: Vec<WordReactions>,
+    /// Share of the messages of each sender the actor reacted to, {actor: {sender: rate}}.
+    pub reciprocity: HashMap<String, HashMap<String, f64>>,
+}
+
+fn get_length_label(idx: usize) -> String {
+    return match idx {
+        0 => format!("<{}", LENGTH_BOUNDS[0]),
+        idx if idx == LENGTH_BOUNDS.len() => format!("{}+", LENGTH_BOUNDS[idx - 1]),
+        idx => format!("{}-{}", LENGTH_BOUNDS[idx - 1], LENGTH_BOUNDS[idx] - 1),
+    };
+}
+
+fn get_rates(labels: Vec<String>, counts: Vec<(i64, i64)>) -> Vec<ReactionRate> {
+    return labels
+        .into_iter()
+        .zip(counts)
+        .map(|(label, (messages, reacted))| ReactionRate {
+            label,
+            messages,
+            reacted,
+            rate: if messages > 0 {
+                reacted as f64 / messages as f64
+            } else {
+                0.0
+            },
+        })
+        .collect();
+}
+
+/// What gets reacted to: a message counts as reacted when at least one other participant
+/// reacted to it, whatever the number of reactions.
+pub fn get_reacted_to_stats(
+    messages: &Vec<ReactedMessage>,
+    stop_words: &StopWords,
+    num_messages: usize,
+    num_words: usize,
+    min_word_messages: i64,
+) -> ReactedToStats {
+    let tokenizer_options = TokenizerOptions::default();
+    let mut by_length = vec![(0, 0); LENGTH_BOUNDS.len() + 1];
+    let mut by_hour = vec![(0, 0); 24];
+    // Text, link and media.
+    let mut by_content = vec![(0, 0); 3];
+    let mut words: HashMap<String, (i64, i64)> = HashMap::new();
+    let mut sent: HashMap<&String, i64> = HashMap::new();
+    let mut reacted_pairs: HashMap<(&String, &String), i64> = HashMap::new();
+    let mut num_reacted = 0;
+
+    for msg in messages {
+        let reacted = if msg.actors.is_empty() { 0 } else { 1 };
+        num_reacted += reacted;
+        *sent.entry(&msg.sender_name).or_insert(0) += 1;
+        let actors: HashSet<&String> = msg.actors.iter().collect();
+        for actor in actors {
+            *reacted_pairs.entry((actor, &msg.sender_name)).or_insert(0) += 1;
+        }
+
+        if let Some(datetime) = DateTime::from_timestamp_millis(msg.timestamp_ms) {
+            let counts = &mut by_hour[datetime.hour() as usize];
+            counts.0 += 1;
+            counts.1 += reacted;
+        }
+
+        let content_idx = match &msg.content {
+            _ if msg.has_media => Some(2),
+            Some(content) if !get_links(content).is_empty() => Some(1),
+            Some(_) => Some(0),
+            None => None,
+        };
+        if let Some(idx) = content_idx {
+            by_content[idx].0 += 1;
+            by_content[idx].1 += reacted;
+        }
+
+        if let Some(content) = &msg.content {
+            let length = content.chars().count();
+            let idx = LENGTH_BOUNDS
+                .iter()
+                .position(|bound| length < *bound)
+                .unwrap_or(LENGTH_BOUNDS.len());
+            by_length[idx].0 += 1;
+            by_length[idx].1 += reacted;
+
+            let tokens: HashSet<String> = tokenize(content, &tokenizer_options)
+                .into_iter()
+                .filter(|word| !stop_words.contains(word))
+                .collect();
+            for word in tokens {
+                let counts = words.entry(word).or_insert((0, 0));
+                counts.0 += 1;
+                counts.1 += reacted;
+            }
+        }
+    }
+
+    let mut stats = ReactedToStats {
+        overall_rate: if messages.is_empty() {
+            0.0
+        } else {
+            num_reacted as f64 / messages.len() as f64
+        },
+        by_length: get_rates(
+            (0..=LENGTH_BOUNDS.len()).map(get_length_label).collect(),
+            by_length,
+        ),
+        by_hour: get_rates(
+            (0..24).map(|hour| format!("{:02}", hour)).collect(),
+            by_hour,
+        ),
+        by_content: get_rates(
+            vec!["text".to_string(), "link".to_string(), "media".to_string()],
+            by_content,
+        ),
+        ..ReactedToStats::default()
+    };
+
+    if stats.overall_rate > 0.0 {
+        let mut top_words: Vec<WordReactions> = words
+            .into_iter()
+            .filter(|(_, (count, _))| *count >= min_word_messages)
+            .map(|(word, (count, reacted))| {
+                let smoothed = (reacted as f64 + PRIOR_WEIGHT * stats.overall_rate)
+                    / (count as f64 + PRIOR_WEIGHT);
+                WordReactions {
+                    word,
+                    messages: count,
+                    rate: reacted as f64 / count as f64,
+                    lift: smoothed / stats.overall_rate,
+                }
+            })
+            .collect();
+        top_words.sort_by(|a, b| b.lift.total_cmp(&a.lift).then_with(|| a.word.cmp(&b.word)));
+        top_words.truncate(num_words);
+        stats.top_words = top_words;
+    }
+
+    let mut top_messages: Vec<&ReactedMessage> = messages
+        .iter()
+        .filter(|msg| !msg.actors.is_empty())
+        .collect();
+    top_messages.sort_by(|a, b| {
+        b.actors
+            .len()
+            .cmp(&a.actors.len())
+            .then_with(|| a.timestamp_ms.cmp(&b.timestamp_ms))
+    });
+    stats.top_messages = top_messages
+        .into_iter()
+        .take(num_messages)
+        .cloned()
+        .collect();
+
+    for ((actor, sender), count) in reacted_pairs {
+        stats
+            .reciprocity
+            .entry(actor.clone())
+            .or_default()
+            .insert(sender.clone(), count as f64 / sent[sender] as f64);
+    }
+
+    return stats;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_reacted_to_stats, ReactedMessage};
+    use crate::analysis::stopwords::{Language, StopWords};
+
+    #[test]
+    fn test_reacted_to_stats() {
+        let hour = 60 * 60 * 1000;
+        let messages: Vec<ReactedMessage> = [
+            ("p1", 0, Some("the cat is back"), false, vec!["p2"]),
+            ("p1", hour, Some("my cat sleeps"), false, vec!["p2", "p3"]),
+            ("p2", hour, Some("ok"), false, vec![]),
+            (
+                "p2",
+                2 * hour,
+                Some("see https://example.com"),
+                false,
+                vec![],
+            ),
+            ("p3", 2 * hour, None, true, vec!["p1", "p2"]),
+            ("p3", 2 * hour, Some("the dog is there"), false, vec![]),
+        ]
+        .iter()
+        .map(
+            |(sender, timestamp_ms, content, has_media, actors)| ReactedMessage {
+                sender_name: sender.to_string(),
+                timestamp_ms: *timestamp_ms,
+                content: content.map(|text| text.to_string()),
+                has_media: *has_media,
+                actors: actors.iter().map(|actor| actor.to_string()).collect(),
+            },
+        )
+        .collect();
+        let stop_words = StopWords::new(&[Language::English]);
+
+        let stats = get_reacted_to_stats(&messages, &stop_words, 2, 10, 2);
+
+        assert_eq!(stats.overall_rate, 0.5);
+        assert_eq!(stats.by_length[0].label, "<10");
+        assert_eq!(stats.by_length[0].messages, 1);
+        assert_eq!(stats.by_length[1].label, "10-49");
+        assert_eq!(stats.by_length[1].rate, 0.5);
+        assert_eq!(stats.by_hour.len(), 24);
+        assert_eq!(stats.by_hour[2].reacted, 1);
+        let rates: Vec<f64> = stats.by_content.iter().map(|rate| rate.rate).collect();
+        assert_eq!(rates, vec![0.5, 0.0, 1.0]);
+
+        assert_eq!(stats.top_messages.len(), 2);
+        assert_eq!(stats.top_messages[0].actors, vec!["p2", "p3"]);
+        // "cat" is in the only two texts with reactions, the stop words are ignored.
+        assert_eq!(stats.top_words.len(), 1);
+        assert_eq!(stats.top_words[0].word, "cat");
+        assert!(stats.top_words[0].lift > 1.0);
+
+        assert_eq!(stats.reciprocity["p2"]["p1"], 1.0);
+        assert_eq!(stats.reciprocity["p2"]["p3"], 0.5);
+        assert!(!stats.reciprocity.contains_key("p4"));
+    }
+}
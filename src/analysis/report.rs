@@ -0,0 +1,491 @@
+use crate::analysis::attribution::{get_attribution_stats, AttributionStats, DEFAULT_TEST_EVERY};
+use crate::analysis::distinctive::{get_distinctive_terms, DistinctiveTerms};
+use crate::analysis::emojis::{get_emoji_stats, EmojiStats};
+use crate::analysis::filter::MessageFilter;
+use crate::analysis::graph::{get_interaction_graph, InteractionGraph};
+use crate::analysis::heatmap::{get_activity_heatmap, ActivityHeatmap};
+use crate::analysis::media::{get_media_stats, MediaStats};
+use crate::analysis::milestones::{get_milestones, Milestone};
+use crate::analysis::ngrams::{get_phrase_stats, PhraseStats};
+use crate::analysis::reacted::{get_reacted_to_stats, ReactedToStats, DEFAULT_MIN_WORD_MESSAGES};
+use crate::analysis::sentiment::{get_sentiment_stats, SentimentLexicon, SentimentStats};
+use crate::analysis::sessions::DEFAULT_SESSION_GAP_MS;
+use crate::analysis::stats::{get_box_summaries, BoxSummary};
+use crate::analysis::stopwords::{Language, StopWords};
+use crate::analysis::style::{get_writing_styles, WritingStyle};
+use crate::analysis::summary::{summarize_periods, PeriodSummary, DEFAULT_SUMMARY_MESSAGES};
+use crate::analysis::survival::{get_reply_survival, SurvivalCurve, DEFAULT_CENSOR_MS};
+use crate::analysis::timeline::{get_timeline, Granularity, Timeline};
+use crate::analysis::topics::{get_topics, TopicOptions, Topics};
+use crate::analysis::turns::{get_turn_taking, TurnTaking};
+use crate::analysis::unanswered::{get_unanswered_stats, UnansweredStats, DEFAULT_REPLY_WINDOW_MS};
+use crate::parsers::base::{get_frequent_words, BaseMessage};
+use crate::parsers::facebook::{
+    get_facebook_media, get_messenger_activity, get_reacted_messages, get_reaction_stats,
+    parse_facebook,
+};
+use crate::parsers::whatsapp::{get_whatsapp_media, parse_whatsapp};
+use chrono::{DateTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Version of the `ConversationReport` schema, to bump whenever a field is renamed,
+/// removed or changes meaning so that saved reports can be told apart.
+pub const REPORT_VERSION: u32 = 3;
+
+/// Gibbs sampling sweeps of the report topics: the report is built on every load, and
+/// the top words of the topics settle well before the 100 sweeps of a standalone run.
+const REPORT_TOPIC_ITERATIONS: usize = 25;
+
+#[derive(Clone, Debug)]
+pub struct ReportOptions {
+    /// Stop words added to the ones of the detected languages, one per line.
+    pub extra_stop_words: String,
+    /// Number of frequent words kept per participant.
+    pub num_words: usize,
+    /// Number of distinctive terms and phrases kept per participant.
+    pub num_terms: usize,
+    pub num_emojis: usize,
+    /// Number of most shared website domains kept.
+    pub num_domains: usize,
+    /// Pause between two messages starting a new session.
+    pub gap_ms: i64,
+    /// A burst without any reply within this window is unanswered.
+    pub reply_window_ms: i64,
+    /// Number of longest silences kept.
+    pub num_silences: usize,
+    /// Number of most reacted messages kept.
+    pub num_reacted_messages: usize,
+    /// Number of most discriminative character n-grams kept per participant.
+    pub num_features: usize,
+    /// Waiting time after which reply times are censored.
+    pub censor_ms: i64,
+    /// Granularity and rolling average window of each timeline.
+    pub timelines: Vec<(Granularity, usize)>,
+    /// Periods summarised by their most representative messages.
+    pub summary_granularity: Granularity,
+    pub num_summary_messages: usize,
+    /// Emojis whose first use is a milestone.
+    pub milestone_emojis: Vec<String>,
+    /// Topic model options, with fewer sweeps than a standalone run by default.
+    pub topics: TopicOptions,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        return ReportOptions {
+            extra_stop_words: String::new(),
+            num_words: 15,
+            num_terms: 15,
+            num_emojis: 15,
+            num_domains: 10,
+            gap_ms: DEFAULT_SESSION_GAP_MS,
+            reply_window_ms: DEFAULT_REPLY_WINDOW_MS,
+            num_silences: 10,
+            num_reacted_messages: 10,
+            num_features: 10,
+            censor_ms: DEFAULT_CENSOR_MS,
+            timelines: vec![(Granularity::Day, 7), (Granularity::Month, 3)],
+            summary_granularity: Granularity::Month,
+            num_summary_messages: DEFAULT_SUMMARY_MESSAGES,
+            milestone_emojis: vec!["❤️".to_string()],
+            topics: TopicOptions {
+                num_iterations: REPORT_TOPIC_ITERATIONS,
+                ..TopicOptions::default()
+            },
+        };
+    }
+}
+
+/// Facebook reactions, reactions to one's own messages are ignored.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ReactionStats {
+    /// Number of reactions given by each actor.
+    pub counts: HashMap<String, i32>,
+    /// {actor: {reaction: count}}
+    pub breakdown: HashMap<String, HashMap<String, i32>>,
+    /// {actor: {sender: count}}
+    pub matrix: HashMap<String, HashMap<String, i32>>,
+}
+
+/// Facebook calls, unsent messages and stickers.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MessengerActivity {
+    /// Number of calls started by each participant, missed ones included.
+    pub calls: HashMap<String, i32>,
+    /// Calls started by each participant that nobody answered.
+    pub missed_calls: HashMap<String, i32>,
+    /// Total duration (in seconds) of the answered calls started by each participant.
+    pub total_call_duration_s: HashMap<String, i64>,
+    pub median_call_duration_s: HashMap<String, f64>,
+    /// Share of the messages of each participant that they unsent.
+    pub unsent_rate: HashMap<String, f64>,
+    /// Number of stickers sent by each participant.
+    pub stickers: HashMap<String, i32>,
+}
+
+/// Every metric of a conversation, rendered as is by the CLI, the web pages and the
+/// JSON export. Fields missing from older reports are left empty.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ConversationReport {
+    pub version: u32,
+    /// Sorted names of the participants.
+    pub participants: Vec<String>,
+    pub num_messages: usize,
+    /// Codes of the detected languages.
+    pub languages: Vec<String>,
+    pub message_counts: HashMap<String, i32>,
+    /// Hour (0-23) of every message of each participant.
+    pub send_hours: HashMap<String, Vec<i64>>,
+    /// Length (in graphemes) of every text of each participant, since version 2 (bytes before).
+    pub message_lengths: HashMap<String, Vec<i64>>,
+    /// Time (in seconds) each participant takes to answer someone else, within a session.
+    pub response_times: HashMap<String, Vec<i64>>,
+    pub response_time_summaries: HashMap<String, BoxSummary>,
+    pub frequent_words: HashMap<String, Vec<String>>,
+    pub distinctive_words: DistinctiveTerms,
+    pub distinctive_bigrams: DistinctiveTerms,
+    pub phrases: PhraseStats,
+    pub emojis: EmojiStats,
+    /// Lexical richness, punctuation, laughter... of each participant.
+    pub styles: HashMap<String, WritingStyle>,
+    /// How well the author of a text can be guessed from its character n-grams.
+    pub attribution: AttributionStats,
+    pub sentiment: SentimentStats,
+    pub topics: Topics,
+    /// One timeline per `ReportOptions::timelines`, in the same order.
+    pub timelines: Vec<Timeline>,
+    /// One summary per period of `ReportOptions::summary_granularity` with messages.
+    pub summaries: Vec<PeriodSummary>,
+    /// First message, longest silence, birthdays... in chronological order.
+    pub milestones: Vec<Milestone>,
+    pub activity_heatmap: ActivityHeatmap,
+    /// Without an unanswered rate since version 3, see `unanswered`.
+    pub turns: TurnTaking,
+    pub unanswered: UnansweredStats,
+    /// Who answers or mentions whom.
+    pub interactions: InteractionGraph,
+    pub reply_survival: HashMap<String, SurvivalCurve>,
+    /// Only available for Facebook conversations.
+    pub reactions: Option<ReactionStats>,
+    /// What gets reacted to, only available for Facebook conversations.
+    pub reacted_to: Option<ReactedToStats>,
+    /// Only available for Facebook conversations.
+    pub messenger_activity: Option<MessengerActivity>,
+    /// Photos, videos, links... shared by each participant.
+    pub media: MediaStats,
+}
+
+/// Compute the report of chronologically sorted messages. The per message metrics are
+/// gathered in a single walk over the messages; the text analyses need the whole
+/// conversation (document frequencies, topics) and run on it afterwards.
+pub fn get_report(
+    messages: &Vec<BaseMessage>,
+    participants: &HashSet<String>,
+    options: &ReportOptions,
+) -> ConversationReport {
+    let mut participants: Vec<String> = participants.iter().cloned().collect();
+    participants.sort();
+
+    let mut report = ConversationReport {
+        version: REPORT_VERSION,
+        num_messages: messages.len(),
+        ..ConversationReport::default()
+    };
+    for name in participants.iter() {
+        report.send_hours.insert(name.clone(), vec![]);
+        report.response_times.insert(name.clone(), vec![]);
+    }
+
+    let mut previous: Option<&BaseMessage> = None;
+    for msg in messages {
+        // Only clone the sender name the first time it is seen.
+        let counted = match report.message_counts.get_mut(&msg.sender_name) {
+            Some(count) => {
+                *count += 1;
+                true
+            }
+            None => false,
+        };
+        if !counted {
+            report.message_counts.insert(msg.sender_name.clone(), 1);
+        }
+
+        if let Some(hours) = report.send_hours.get_mut(&msg.sender_name) {
+            if let Some(datetime) = DateTime::from_timestamp_millis(msg.timestamp_ms) {
+                hours.push(datetime.hour() as i64);
+            }
+        }
+
+        if let Some(content) = &msg.content {
+            let length = content.graphemes(true).count() as i64;
+            match report.message_lengths.get_mut(&msg.sender_name) {
+                Some(lengths) => lengths.push(length),
+                None => {
+                    report
+                        .message_lengths
+                        .insert(msg.sender_name.clone(), vec![length]);
+                }
+            }
+        }
+
+        if let Some(previous_msg) = previous {
+            let response_delta_in_ms = msg.timestamp_ms - previous_msg.timestamp_ms;
+            if previous_msg.sender_name != msg.sender_name && response_delta_in_ms <= options.gap_ms
+            {
+                if let Some(response_times) = report.response_times.get_mut(&msg.sender_name) {
+                    response_times.push(response_delta_in_ms / 1000);
+                }
+            }
+        }
+        previous = Some(msg);
+    }
+    report.response_time_summaries = get_box_summaries(&report.response_times);
+    report.participants = participants;
+
+    let mut stop_words = StopWords::detect(messages);
+    stop_words.add_words(&options.extra_stop_words);
+    report.languages = stop_words
+        .languages
+        .iter()
+        .map(|language| language.code().to_string())
+        .collect();
+    report.frequent_words = get_frequent_words(messages, options.num_words, &stop_words);
+    report.distinctive_words = get_distinctive_terms(messages, options.num_terms, &stop_words, 1);
+    report.distinctive_bigrams = get_distinctive_terms(messages, options.num_terms, &stop_words, 2);
+    report.phrases = get_phrase_stats(messages, &stop_words, options.num_terms);
+    report.emojis = get_emoji_stats(messages, options.num_emojis);
+    report.styles = get_writing_styles(messages);
+    report.attribution = get_attribution_stats(messages, DEFAULT_TEST_EVERY, options.num_features);
+    report.sentiment = get_sentiment_stats(
+        messages,
+        &SentimentLexicon::new(&stop_words.languages),
+        options.gap_ms,
+    );
+    report.topics = get_topics(messages, &stop_words, &options.topics);
+    report.summaries = summarize_periods(
+        messages,
+        options.summary_granularity,
+        &stop_words,
+        options.num_summary_messages,
+    );
+
+    report.timelines = options
+        .timelines
+        .iter()
+        .map(|(granularity, window)| get_timeline(messages, *granularity, *window))
+        .collect();
+    report.activity_heatmap = get_activity_heatmap(messages);
+    report.milestones = get_milestones(messages, &options.milestone_emojis, options.gap_ms);
+    report.turns = get_turn_taking(messages, options.gap_ms);
+    report.unanswered =
+        get_unanswered_stats(messages, options.reply_window_ms, options.num_silences);
+    report.reply_survival = get_reply_survival(messages, options.censor_ms);
+    report.interactions = get_interaction_graph(
+        messages,
+        &report.participants.iter().cloned().collect(),
+        options.reply_window_ms,
+    );
+
+    return report;
+}
+
+/// Report of the `message_x.json` files of a Facebook conversation, with the filtered
+/// messages it was computed from.
+pub fn get_facebook_report(
+    files: Vec<String>,
+    filter: &MessageFilter,
+    options: &ReportOptions,
+) -> (ConversationReport, Vec<BaseMessage>) {
+    let (mut messages, mut participants) = parse_facebook(files);
+    messages.retain(|msg| msg.matches(filter));
+    participants.retain(|name| filter.keeps_participant(name));
+
+    let reactions = get_reaction_stats(&messages);
+    let messenger_activity = get_messenger_activity(&messages);
+    let reacted_messages = get_reacted_messages(&messages);
+    let media = get_media_stats(&get_facebook_media(&messages), options.num_domains);
+    let base_messages: Vec<BaseMessage> = messages.into_iter().map(|m| m.into()).collect();
+
+    let mut report = get_report(&base_messages, &participants, options);
+    report.reactions = Some(reactions);
+    report.messenger_activity = Some(messenger_activity);
+
+    // Same stop words as the text analyses of the report, without detecting them again.
+    let languages: Vec<Language> = report
+        .languages
+        .iter()
+        .filter_map(|code| Language::from_code(code))
+        .collect();
+    let mut stop_words = StopWords::new(&languages);
+    stop_words.add_words(&options.extra_stop_words);
+    report.reacted_to = Some(get_reacted_to_stats(
+        &reacted_messages,
+        &stop_words,
+        options.num_reacted_messages,
+        options.num_terms,
+        DEFAULT_MIN_WORD_MESSAGES,
+    ));
+    report.media = media;
+    return (report, base_messages);
+}
+
+/// Filtered messages of a Facebook conversation, for the analyses run on demand.
+pub fn get_facebook_messages(files: Vec<String>, filter: &MessageFilter) -> Vec<BaseMessage> {
+    let (messages, _) = parse_facebook(files);
+    return messages
+        .into_iter()
+        .filter(|msg| msg.matches(filter))
+        .map(|msg| msg.into())
+        .collect();
+}
+
+/// Report of exported WhatsApp discussions, with the filtered messages it was computed from.
+pub fn get_whatsapp_report(
+    texts: Vec<String>,
+    filter: &MessageFilter,
+    options: &ReportOptions,
+) -> (ConversationReport, Vec<BaseMessage>) {
+    let (mut messages, mut participants) = parse_whatsapp(texts);
+    messages.retain(|msg| msg.matches(filter));
+    participants.retain(|name| filter.keeps_participant(name));
+
+    let media = get_media_stats(&get_whatsapp_media(&messages), options.num_domains);
+    let base_messages: Vec<BaseMessage> = messages.into_iter().map(|m| m.base_message).collect();
+
+    let mut report = get_report(&base_messages, &participants, options);
+    report.media = media;
+    return (report, base_messages);
+}
+
+/// Filtered messages of exported WhatsApp discussions, for the analyses run on demand.
+pub fn get_whatsapp_messages(texts: Vec<String>, filter: &MessageFilter) -> Vec<BaseMessage> {
+    let (messages, _) = parse_whatsapp(texts);
+    return messages
+        .into_iter()
+        .filter(|msg| msg.matches(filter))
+        .map(|msg| msg.base_message)
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_facebook_report, get_report, ConversationReport, ReportOptions, REPORT_VERSION,
+    };
+    use crate::analysis::filter::MessageFilter;
+    use crate::analysis::fixtures::message;
+    use crate::analysis::topics::TopicOptions;
+    use crate::parsers::base::{
+        get_message_counts, get_message_response_times, get_messages_length, get_send_hours,
+        BaseMessage,
+    };
+    use std::collections::HashSet;
+    use std::fs::read_to_string;
+
+    #[test]
+    fn test_report() {
+        let minute = 60 * 1000;
+        let messages: Vec<BaseMessage> = [
+            ("p1", 0, Some("Are you coming tonight?")),
+            ("p1", minute, Some("we are at the bar")),
+            ("p2", 3 * minute, Some("yes, coming")),
+            ("p2", 4 * minute, None),
+            ("p1", 5 * 60 * minute, Some("where are you?")),
+            ("p3", 5 * 60 * minute + 30_000, Some("here")),
+        ]
+        .iter()
+        .map(|(sender, timestamp_ms, content)| message(sender, *timestamp_ms, *content))
+        .collect();
+        // p3 left the conversation.
+        let participants: HashSet<String> = ["p1".to_string(), "p2".to_string()].into();
+        let options = ReportOptions {
+            topics: TopicOptions {
+                num_topics: 2,
+                num_iterations: 10,
+                ..TopicOptions::default()
+            },
+            ..ReportOptions::default()
+        };
+
+        let report = get_report(&messages, &participants, &options);
+
+        assert_eq!(report.version, REPORT_VERSION);
+        assert_eq!(report.participants, vec!["p1", "p2"]);
+        assert_eq!(report.num_messages, 6);
+        // The single pass gives the same metrics as the individual functions.
+        assert_eq!(report.message_counts, get_message_counts(&messages));
+        assert_eq!(report.send_hours, get_send_hours(&messages, &participants));
+        assert_eq!(report.message_lengths, get_messages_length(&messages));
+        assert_eq!(
+            report.response_times,
+            get_message_response_times(&messages, &participants, options.gap_ms)
+        );
+        assert_eq!(report.response_times["p2"], vec![120]);
+        assert_eq!(report.timelines.len(), 2);
+        assert_eq!(report.summaries.len(), 1);
+        assert_eq!(report.summaries[0].num_messages, 6);
+        assert_eq!(
+            report.milestones[0].content.as_deref(),
+            Some("Are you coming tonight?")
+        );
+        assert!(report.reactions.is_none());
+        assert!(report.messenger_activity.is_none());
+        assert!(report.reacted_to.is_none());
+
+        let json = serde_json::to_string(&report).unwrap();
+        let parsed: ConversationReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.version, REPORT_VERSION);
+        assert_eq!(parsed.message_counts, report.message_counts);
+    }
+
+    #[test]
+    fn test_facebook_report() {
+        let files = vec![read_to_string("./tests/assets/message_1.json").unwrap()];
+
+        let (report, messages) = get_facebook_report(
+            files.clone(),
+            &MessageFilter::default(),
+            &ReportOptions::default(),
+        );
+        assert_eq!(report.participants.len(), 2);
+        assert_eq!(messages.len(), report.num_messages);
+        assert!(report.reactions.is_some());
+        assert_eq!(
+            report
+                .messenger_activity
+                .as_ref()
+                .unwrap()
+                .calls
+                .values()
+                .sum::<i32>(),
+            44
+        );
+        let reacted_to = report.reacted_to.as_ref().unwrap();
+        assert_eq!(reacted_to.top_messages.len(), 10);
+        assert!(reacted_to.reciprocity["Participant_2"]["Participant_1"] > 0.0);
+        assert!(report
+            .media
+            .counts
+            .values()
+            .any(|counts| counts.contains_key("photo")));
+        assert!(!report.media.top_domains.is_empty());
+        // Every text of the asset is "some content": nothing to learn, but one text out of
+        // five is still held out.
+        assert_eq!(report.attribution.participant_accuracy.len(), 2);
+        assert!(report.attribution.num_tested > 1000);
+
+        let only_first = MessageFilter {
+            participants: Some([report.participants[0].clone()].into()),
+            ..MessageFilter::default()
+        };
+        let (filtered, _) = get_facebook_report(files, &only_first, &ReportOptions::default());
+        assert_eq!(filtered.participants, vec![report.participants[0].clone()]);
+        assert_eq!(
+            filtered.num_messages as i32,
+            report.message_counts[&report.participants[0]]
+        );
+    }
+}
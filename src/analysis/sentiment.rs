@@ -0,0 +1,423 @@
+use crate::analysis::emojis::get_emojis;
+use crate::analysis::sessions::get_sessions;
+use crate::analysis::stopwords::Language;
+use crate::analysis::tokenizer::{split_elision, tokenize, TokenizerOptions};
+use crate::parsers::base::BaseMessage;
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Normalisation constant of the compound score, as in VADER.
+const COMPOUND_ALPHA: f64 = 15.0;
+/// Multiplier of the valence of a negated word.
+const NEGATION_SCALAR: f64 = -0.74;
+/// Valence added by an intensifier ("very"), removed by a dampener ("slightly").
+const BOOSTER_INCREMENT: f64 = 0.293;
+/// Valence added by each exclamation mark.
+const EXCLAMATION_INCREMENT: f64 = 0.292;
+const MAX_EXCLAMATIONS: usize = 4;
+/// Number of words before a sentiment word in which negations and intensifiers apply.
+const SCOPE: usize = 3;
+/// Number of words after a sentiment word in which trailing negations apply.
+const TRAILING_SCOPE: usize = 1;
+
+// cSpell: disable
+const NEGATIONS_EN: &[&str] = &[
+    "not",
+    "no",
+    "never",
+    "nothing",
+    "nobody",
+    "none",
+    "nor",
+    "neither",
+    "without",
+    "don't",
+    "doesn't",
+    "didn't",
+    "isn't",
+    "aren't",
+    "wasn't",
+    "weren't",
+    "can't",
+    "cannot",
+    "couldn't",
+    "won't",
+    "wouldn't",
+    "shouldn't",
+    "haven't",
+    "hasn't",
+    "hadn't",
+    "ain't",
+    "dont",
+    "doesnt",
+    "didnt",
+    "isnt",
+    "cant",
+    "wont",
+];
+const NEGATIONS_FR: &[&str] = &[
+    "ne", "n'", "pas", "jamais", "rien", "personne", "aucun", "aucune", "sans", "ni",
+];
+/// Negations following the negated word: "j'aime pas". "plus" only negates after "ne",
+/// which negates by itself ("je n'aime plus"): alone it compares ("bien plus simple").
+const TRAILING_NEGATIONS_FR: &[&str] = &["pas", "jamais", "rien"];
+/// Valence of words whose negation does not flip their meaning: "pas terrible" is meh.
+const NEGATED_VALENCES_FR: &[(&str, f64)] = &[("terrible", -1.0)];
+const BOOSTERS_EN: &[&str] = &[
+    "very",
+    "really",
+    "so",
+    "soo",
+    "sooo",
+    "too",
+    "extremely",
+    "totally",
+    "absolutely",
+    "completely",
+    "incredibly",
+    "truly",
+    "such",
+    "most",
+    "fucking",
+];
+const BOOSTERS_FR: &[&str] = &[
+    "très",
+    "vraiment",
+    "trop",
+    "tellement",
+    "hyper",
+    "vachement",
+    "totalement",
+    "complètement",
+    "si",
+    "tant",
+];
+const DAMPENERS_EN: &[&str] = &[
+    "slightly", "somewhat", "kinda", "barely", "hardly", "bit", "little", "almost",
+];
+const DAMPENERS_FR: &[&str] = &["peu", "plutôt", "moyennement", "presque", "assez"];
+// cSpell: enable
+
+/// Sentiment lexicons of the languages of a conversation (English and French are bundled,
+/// emojis are always scored), with their negations and intensifiers.
+#[derive(Clone, Debug, Default)]
+pub struct SentimentLexicon {
+    pub languages: Vec<Language>,
+    words: HashMap<String, f64>,
+    emojis: HashMap<String, f64>,
+    negations: HashSet<&'static str>,
+    trailing_negations: HashSet<&'static str>,
+    negated_valences: HashMap<&'static str, f64>,
+    /// Positive for intensifiers, negative for dampeners.
+    boosters: HashMap<&'static str, f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SentimentStats {
+    /// Mean compound score (from -1 to 1) of the messages of each participant.
+    pub per_participant: HashMap<String, f64>,
+    /// Mean compound score per ISO week (%G-W%V), per participant.
+    pub per_week: HashMap<String, BTreeMap<String, f64>>,
+    /// Mean compound score of each conversation session, with the session start timestamp.
+    pub per_session: Vec<(i64, f64)>,
+}
+
+/// "word valence" lines, `#` starts a comment.
+fn parse_lexicon(text: &str) -> Vec<(&str, f64)> {
+    return text
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or(""))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let word = fields.next()?;
+            let valence = fields.next()?.parse::<f64>().ok()?;
+            Some((word, valence))
+        })
+        .collect();
+}
+
+/// Skin tones and emoji presentation selectors do not change the meaning of an emoji.
+fn normalize_emoji(emoji: &str) -> String {
+    return emoji
+        .chars()
+        .filter(|c| !matches!(*c as u32, 0xFE0F | 0x1F3FB..=0x1F3FF))
+        .collect();
+}
+
+impl SentimentLexicon {
+    pub fn new(languages: &[Language]) -> SentimentLexicon {
+        let mut lexicon = SentimentLexicon {
+            languages: languages.to_vec(),
+            ..SentimentLexicon::default()
+        };
+        let word_options = TokenizerOptions {
+            split_elisions: false,
+            ..TokenizerOptions::default()
+        };
+
+        for language in languages {
+            let (text, negations, trailing_negations, negated_valences, boosters, dampeners) =
+                match language {
+                    Language::English => (
+                        include_str!("sentiment/en.txt"),
+                        NEGATIONS_EN,
+                        [].as_slice(),
+                        [].as_slice(),
+                        BOOSTERS_EN,
+                        DAMPENERS_EN,
+                    ),
+                    Language::French => (
+                        include_str!("sentiment/fr.txt"),
+                        NEGATIONS_FR,
+                        TRAILING_NEGATIONS_FR,
+                        NEGATED_VALENCES_FR,
+                        BOOSTERS_FR,
+                        DAMPENERS_FR,
+                    ),
+                    _ => continue,
+                };
+
+            for (word, valence) in parse_lexicon(text) {
+                if let Some(word) = tokenize(word, &word_options).into_iter().next() {
+                    lexicon.words.insert(word, valence);
+                }
+            }
+            lexicon.negations.extend(negations.iter());
+            lexicon.trailing_negations.extend(trailing_negations.iter());
+            lexicon.negated_valences.extend(negated_valences.iter().copied());
+            lexicon
+                .boosters
+                .extend(boosters.iter().map(|word| (*word, BOOSTER_INCREMENT)));
+            lexicon
+                .boosters
+                .extend(dampeners.iter().map(|word| (*word, -BOOSTER_INCREMENT)));
+        }
+
+        for (emoji, valence) in parse_lexicon(include_str!("sentiment/emojis.txt")) {
+            lexicon.emojis.insert(normalize_emoji(emoji), valence);
+        }
+
+        return lexicon;
+    }
+
+    /// VADER-like compound score of a text, from -1 (most negative) to 1 (most positive).
+    /// Returns `None` when the text has no word or emoji carrying a sentiment.
+    pub fn score(&self, text: &str) -> Option<f64> {
+        let options = TokenizerOptions {
+            split_elisions: false,
+            ..TokenizerOptions::default()
+        };
+        // Elided negations are words of their own: "n'aime" => "n'", "aime".
+        let mut tokens: Vec<String> = Vec::new();
+        for token in tokenize(text, &options) {
+            match split_elision(&token) {
+                Some((prefix, word)) => {
+                    let prefix = format!("{}'", prefix);
+                    if self.negations.contains(prefix.as_str()) {
+                        tokens.push(prefix);
+                    }
+                    tokens.push(word.to_string());
+                }
+                None => tokens.push(token),
+            }
+        }
+        let mut valences: Vec<f64> = Vec::new();
+
+        for (idx, token) in tokens.iter().enumerate() {
+            let mut valence = match self.words.get(token) {
+                Some(valence) => *valence,
+                None => continue,
+            };
+
+            let scope = &tokens[idx.saturating_sub(SCOPE)..idx];
+            // Closer intensifiers weigh more: "very very good" > "very good".
+            for (distance, previous) in scope.iter().rev().enumerate() {
+                if let Some(increment) = self.boosters.get(previous.as_str()) {
+                    valence += valence.signum() * increment * (1.0 - 0.05 * distance as f64);
+                }
+            }
+            let trailing_scope = &tokens[idx + 1..(idx + 1 + TRAILING_SCOPE).min(tokens.len())];
+            // "je n'aime pas" is negated once.
+            if scope
+                .iter()
+                .any(|previous| self.negations.contains(previous.as_str()))
+                || trailing_scope
+                    .iter()
+                    .any(|next| self.trailing_negations.contains(next.as_str()))
+            {
+                valence = match self.negated_valences.get(token.as_str()) {
+                    Some(negated_valence) => *negated_valence,
+                    None => valence * NEGATION_SCALAR,
+                };
+            }
+
+            valences.push(valence);
+        }
+
+        for emoji in get_emojis(text) {
+            if let Some(valence) = self.emojis.get(&normalize_emoji(emoji)) {
+                valences.push(*valence);
+            }
+        }
+
+        if valences.is_empty() {
+            return None;
+        }
+
+        let mut sum: f64 = valences.iter().sum();
+        let exclamations = text.matches('!').count().min(MAX_EXCLAMATIONS);
+        sum += sum.signum() * exclamations as f64 * EXCLAMATION_INCREMENT;
+
+        return Some(sum / (sum * sum + COMPOUND_ALPHA).sqrt());
+    }
+}
+
+fn add_score(means: &mut (f64, i64), score: f64) {
+    means.0 += score;
+    means.1 += 1;
+}
+
+fn mean((sum, count): (f64, i64)) -> f64 {
+    return sum / count as f64;
+}
+
+/// Sentiment of chronologically sorted messages. Only the messages carrying a sentiment
+/// are averaged, so neutral small talk does not flatten the scores.
+pub fn get_sentiment_stats(
+    messages: &Vec<BaseMessage>,
+    lexicon: &SentimentLexicon,
+    gap_ms: i64,
+) -> SentimentStats {
+    let scores: Vec<Option<f64>> = messages
+        .iter()
+        .map(|msg| {
+            msg.content
+                .as_ref()
+                .and_then(|content| lexicon.score(content))
+        })
+        .collect();
+
+    let mut participant_scores: HashMap<String, (f64, i64)> = HashMap::new();
+    let mut week_scores: HashMap<String, BTreeMap<String, (f64, i64)>> = HashMap::new();
+
+    for (msg, score) in messages.iter().zip(scores.iter()) {
+        if let Some(score) = score {
+            add_score(
+                participant_scores
+                    .entry(msg.sender_name.clone())
+                    .or_insert((0.0, 0)),
+                *score,
+            );
+
+            if let Some(datetime) = DateTime::from_timestamp_millis(msg.timestamp_ms) {
+                add_score(
+                    week_scores
+                        .entry(msg.sender_name.clone())
+                        .or_default()
+                        .entry(datetime.format("%G-W%V").to_string())
+                        .or_insert((0.0, 0)),
+                    *score,
+                );
+            }
+        }
+    }
+
+    let per_participant = participant_scores
+        .into_iter()
+        .map(|(name, scores)| (name, mean(scores)))
+        .collect();
+    let per_week = week_scores
+        .into_iter()
+        .map(|(name, weeks)| {
+            (
+                name,
+                weeks
+                    .into_iter()
+                    .map(|(week, scores)| (week, mean(scores)))
+                    .collect(),
+            )
+        })
+        .collect();
+
+    let mut per_session: Vec<(i64, f64)> = Vec::new();
+    for session in get_sessions(messages, gap_ms) {
+        let mut session_scores = (0.0, 0);
+        for score in scores[session.clone()].iter().flatten() {
+            add_score(&mut session_scores, *score);
+        }
+        if session_scores.1 > 0 {
+            per_session.push((messages[session.start].timestamp_ms, mean(session_scores)));
+        }
+    }
+
+    return SentimentStats {
+        per_participant,
+        per_week,
+        per_session,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_sentiment_stats, SentimentLexicon};
+    use crate::analysis::fixtures::message;
+    use crate::analysis::stopwords::Language;
+    use crate::parsers::base::BaseMessage;
+
+    #[test]
+    fn test_sentiment_score() {
+        let lexicon = SentimentLexicon::new(&[Language::English, Language::French]);
+
+        let good = lexicon.score("this is good").unwrap();
+        assert!(good > 0.0);
+        assert!(lexicon.score("this is very good").unwrap() > good);
+        assert!(lexicon.score("this is good!!").unwrap() > good);
+        assert!(lexicon.score("this is not good").unwrap() < 0.0);
+        assert!(lexicon.score("I hate mondays").unwrap() < 0.0);
+
+        assert!(lexicon.score("c'est vraiment génial").unwrap() > 0.5);
+        assert!(lexicon.score("je n'aime pas ça").unwrap() < 0.0);
+        assert!(lexicon.score("j'aime pas").unwrap() < 0.0);
+        assert!(lexicon.score("je ne l'aime plus").unwrap() < 0.0);
+        assert!(lexicon.score("ce n'est pas mal").unwrap() > 0.0);
+        assert!(lexicon.score("c'est pas terrible").unwrap() < 0.0);
+        // A comparative "plus" negates nothing.
+        assert!(lexicon.score("c'est bien plus simple").unwrap() > 0.0);
+        assert!(lexicon.score("super plus rapide").unwrap() > 0.0);
+
+        assert!(lexicon.score("😭😭").unwrap() < 0.0);
+        assert!(lexicon.score("👍🏽").unwrap() > 0.0);
+        assert_eq!(lexicon.score("see you at 5"), None);
+
+        let score = lexicon.score("love love love love love love").unwrap();
+        assert!(score < 1.0);
+    }
+
+    #[test]
+    fn test_sentiment_stats() {
+        let lexicon = SentimentLexicon::new(&[Language::English]);
+        let hour = 60 * 60 * 1000;
+        let messages: Vec<BaseMessage> = [
+            ("p1", 1688067261456, "great news!"),
+            ("p2", 1688067261456 + 1000, "awesome"),
+            ("p1", 1688067261456 + 2000, "see you tomorrow"),
+            ("p1", 1688067261456 + 2 * hour, "I feel sad"),
+        ]
+        .iter()
+        .map(|(sender, timestamp_ms, content)| message(sender, *timestamp_ms, Some(content)))
+        .collect();
+
+        let stats = get_sentiment_stats(&messages, &lexicon, hour);
+
+        assert!(stats.per_participant["p2"] > 0.0);
+        assert!(stats.per_participant["p1"] < stats.per_participant["p2"]);
+        assert_eq!(
+            stats.per_week["p1"].keys().collect::<Vec<_>>(),
+            vec!["2023-W26"]
+        );
+        assert_eq!(stats.per_session.len(), 2);
+        assert!(stats.per_session[0].1 > 0.0);
+        assert!(stats.per_session[1].1 < 0.0);
+    }
+}
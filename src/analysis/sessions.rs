@@ -0,0 +1,49 @@
+use crate::parsers::base::BaseMessage;
+use std::ops::Range;
+
+/// A pause longer than this (30 minutes) between two messages starts a new session.
+pub const DEFAULT_SESSION_GAP_MS: i64 = 30 * 60 * 1000;
+
+/// Split chronologically sorted messages into conversation sessions.
+/// A new session starts whenever the pause between two messages is longer than `gap_ms`.
+/// Each session is returned as the range of its indices in `messages`.
+pub fn get_sessions(messages: &Vec<BaseMessage>, gap_ms: i64) -> Vec<Range<usize>> {
+    let mut sessions: Vec<Range<usize>> = Vec::new();
+    let mut start = 0;
+
+    for idx in 1..messages.len() {
+        if messages[idx].timestamp_ms - messages[idx - 1].timestamp_ms > gap_ms {
+            sessions.push(start..idx);
+            start = idx;
+        }
+    }
+
+    if start < messages.len() {
+        sessions.push(start..messages.len());
+    }
+
+    return sessions;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_sessions;
+    use crate::analysis::fixtures::message;
+
+    #[test]
+    fn test_sessions_split_on_gap() {
+        let messages = vec![
+            message("p1", 0, None),
+            message("p2", 1_000, None),
+            message("p1", 100_000, None),
+            message("p2", 101_000, None),
+            message("p2", 102_000, None),
+        ];
+        assert_eq!(get_sessions(&messages, 10_000), vec![0..2, 2..5]);
+    }
+
+    #[test]
+    fn test_sessions_empty() {
+        assert!(get_sessions(&vec![], 10_000).is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Share of the values removed at each end by `trimmed_mean` in summaries.
+pub const DEFAULT_TRIM: f64 = 0.1;
+/// Tukey's fences: values further than this many interquartile ranges from the
+/// quartiles are outliers.
+const OUTLIER_IQR_FACTOR: f64 = 1.5;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BoxSummary {
+    pub count: usize,
+    pub mean: f64,
+    /// Mean without the `DEFAULT_TRIM` lowest and highest values.
+    pub trimmed_mean: f64,
+    pub min: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub max: f64,
+    /// Most extreme values that are not outliers, the ends of the box-plot whiskers.
+    pub lower_whisker: f64,
+    pub upper_whisker: f64,
+    pub num_outliers: usize,
+}
+
+fn sorted(values: &[f64]) -> Vec<f64> {
+    let mut values = values.to_vec();
+    values.sort_by(|a, b| a.total_cmp(b));
+    return values;
+}
+
+/// Linear interpolation between the closest ranks, `q` between 0 and 1.
+fn quantile_of_sorted(sorted_values: &[f64], q: f64) -> Option<f64> {
+    if sorted_values.is_empty() {
+        return None;
+    }
+    let rank = q.clamp(0.0, 1.0) * (sorted_values.len() - 1) as f64;
+    let low = rank.floor() as usize;
+    let high = rank.ceil() as usize;
+    return Some(
+        sorted_values[low] + (sorted_values[high] - sorted_values[low]) * (rank - low as f64),
+    );
+}
+
+/// `q` quantile (0.5 is the median) of unsorted values, `None` when there are none.
+pub fn quantile(values: &[f64], q: f64) -> Option<f64> {
+    return quantile_of_sorted(&sorted(values), q);
+}
+
+pub fn median(values: &[f64]) -> Option<f64> {
+    return quantile(values, 0.5);
+}
+
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    return Some(values.iter().sum::<f64>() / values.len() as f64);
+}
+
+/// Mean of the values once the `proportion` lowest and highest ones are removed.
+pub fn trimmed_mean(values: &[f64], proportion: f64) -> Option<f64> {
+    let values = sorted(values);
+    let trimmed = (values.len() as f64 * proportion.clamp(0.0, 0.5)).floor() as usize;
+    if trimmed * 2 >= values.len() {
+        return median(&values);
+    }
+    return mean(&values[trimmed..values.len() - trimmed]);
+}
+
+fn fences(sorted_values: &[f64]) -> Option<(f64, f64)> {
+    let q1 = quantile_of_sorted(sorted_values, 0.25)?;
+    let q3 = quantile_of_sorted(sorted_values, 0.75)?;
+    let iqr = q3 - q1;
+    return Some((q1 - OUTLIER_IQR_FACTOR * iqr, q3 + OUTLIER_IQR_FACTOR * iqr));
+}
+
+pub fn box_summary(values: &[f64]) -> Option<BoxSummary> {
+    let sorted_values = sorted(values);
+    let (low_fence, high_fence) = fences(&sorted_values)?;
+    let inliers: Vec<f64> = sorted_values
+        .iter()
+        .cloned()
+        .filter(|value| *value >= low_fence && *value <= high_fence)
+        .collect();
+
+    return Some(BoxSummary {
+        count: sorted_values.len(),
+        mean: mean(&sorted_values)?,
+        trimmed_mean: trimmed_mean(&sorted_values, DEFAULT_TRIM)?,
+        min: sorted_values[0],
+        q1: quantile_of_sorted(&sorted_values, 0.25)?,
+        median: quantile_of_sorted(&sorted_values, 0.5)?,
+        q3: quantile_of_sorted(&sorted_values, 0.75)?,
+        max: sorted_values[sorted_values.len() - 1],
+        lower_whisker: inliers[0],
+        upper_whisker: inliers[inliers.len() - 1],
+        num_outliers: sorted_values.len() - inliers.len(),
+    });
+}
+
+/// Values within Tukey's fences, in their original order.
+pub fn without_outliers(values: &[i64]) -> Vec<i64> {
+    let as_f64: Vec<f64> = values.iter().map(|value| *value as f64).collect();
+    return match fences(&sorted(&as_f64)) {
+        Some((low_fence, high_fence)) => values
+            .iter()
+            .cloned()
+            .filter(|value| *value as f64 >= low_fence && *value as f64 <= high_fence)
+            .collect(),
+        None => Vec::new(),
+    };
+}
+
+/// Box-plot summary of the values of each participant (participants without values are left out).
+pub fn get_box_summaries(data: &HashMap<String, Vec<i64>>) -> HashMap<String, BoxSummary> {
+    return data
+        .iter()
+        .filter_map(|(name, values)| {
+            let values: Vec<f64> = values.iter().map(|value| *value as f64).collect();
+            Some((name.clone(), box_summary(&values)?))
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{box_summary, median, quantile, trimmed_mean, without_outliers};
+
+    #[test]
+    fn test_quantiles() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[4.0]), Some(4.0));
+        // Unsorted input.
+        assert_eq!(median(&[3.0, 1.0, 2.0, 10.0]), Some(2.5));
+        assert_eq!(quantile(&[10.0, 0.0, 5.0], 0.25), Some(2.5));
+        assert_eq!(quantile(&[10.0, 0.0, 5.0], 1.0), Some(10.0));
+
+        assert_eq!(
+            trimmed_mean(&[100.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0], 0.1),
+            Some(5.5)
+        );
+        assert_eq!(trimmed_mean(&[1.0, 5.0], 0.5), Some(3.0));
+    }
+
+    #[test]
+    fn test_box_summary() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 100.0];
+        let summary = box_summary(&values).unwrap();
+
+        assert_eq!(summary.count, 10);
+        assert_eq!(summary.median, 5.5);
+        assert_eq!(summary.q1, 3.25);
+        assert_eq!(summary.q3, 7.75);
+        assert_eq!(summary.upper_whisker, 9.0);
+        assert_eq!(summary.num_outliers, 1);
+        assert_eq!(summary.max, 100.0);
+        assert!(box_summary(&[]).is_none());
+
+        assert_eq!(without_outliers(&[5, 100, 1, 2, 3, 4]), vec![5, 1, 2, 3, 4]);
+        assert!(without_outliers(&[]).is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+use crate::analysis::tokenizer::{tokenize, TokenizerOptions};
+use crate::parsers::base::BaseMessage;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Number of tokens looked at to detect the languages of a conversation.
+const DETECTION_SAMPLE_SIZE: usize = 50_000;
+/// A language is detected when it has at least this share of the distinctive
+/// stop-words hits of the most used language.
+const DETECTION_RELATIVE_THRESHOLD: f64 = 0.2;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Language {
+    English,
+    French,
+    Spanish,
+    German,
+    Portuguese,
+}
+
+impl Language {
+    pub const ALL: [Language; 5] = [
+        Language::English,
+        Language::French,
+        Language::Spanish,
+        Language::German,
+        Language::Portuguese,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        return match self {
+            Language::English => "en",
+            Language::French => "fr",
+            Language::Spanish => "es",
+            Language::German => "de",
+            Language::Portuguese => "pt",
+        };
+    }
+
+    pub fn from_code(code: &str) -> Option<Language> {
+        return Language::ALL
+            .into_iter()
+            .find(|language| language.code() == code.to_lowercase());
+    }
+
+    fn bundled_stop_words(&self) -> &'static str {
+        return match self {
+            Language::English => include_str!("stopwords/en.txt"),
+            Language::French => include_str!("stopwords/fr.txt"),
+            Language::Spanish => include_str!("stopwords/es.txt"),
+            Language::German => include_str!("stopwords/de.txt"),
+            Language::Portuguese => include_str!("stopwords/pt.txt"),
+        };
+    }
+
+    fn stop_words(&self) -> impl Iterator<Item = String> {
+        return parse_word_list(self.bundled_stop_words()).into_iter();
+    }
+}
+
+/// One word per line (or separated by whitespace / commas), `#` starts a comment.
+/// Words are normalised like the tokens they will be compared to.
+fn parse_word_list(text: &str) -> Vec<String> {
+    let options = TokenizerOptions {
+        split_elisions: false,
+        keep_numbers: true,
+        ..TokenizerOptions::default()
+    };
+
+    return text
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or(""))
+        .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+        .filter(|word| !word.is_empty())
+        .flat_map(|word| tokenize(word, &options))
+        .collect();
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StopWords {
+    pub languages: Vec<Language>,
+    words: HashSet<String>,
+}
+
+impl StopWords {
+    pub fn new(languages: &[Language]) -> StopWords {
+        let mut stop_words = StopWords {
+            languages: languages.to_vec(),
+            words: HashSet::new(),
+        };
+        for language in languages {
+            stop_words.words.extend(language.stop_words());
+        }
+        return stop_words;
+    }
+
+    /// Stop words for the languages detected in the messages.
+    pub fn detect(messages: &Vec<BaseMessage>) -> StopWords {
+        return StopWords::new(&detect_languages(messages));
+    }
+
+    /// Add user defined stop words (content of a file or of a text field),
+    /// see `parse_word_list` for the format.
+    pub fn add_words(&mut self, text: &str) {
+        self.words.extend(parse_word_list(text));
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        return self.words.contains(word);
+    }
+}
+
+/// Detect the languages used in a conversation from the stop words it contains.
+/// Only the stop words distinctive of a language (absent from the other lists) are
+/// counted, so that "de" or "que" do not make a Spanish chat look French.
+/// Falls back to every bundled language when nothing can be detected.
+pub fn detect_languages(messages: &Vec<BaseMessage>) -> Vec<Language> {
+    let lists: Vec<(Language, HashSet<String>)> = Language::ALL
+        .into_iter()
+        .map(|language| (language, language.stop_words().collect()))
+        .collect();
+
+    let mut occurrences: HashMap<&String, usize> = HashMap::new();
+    for (_, words) in lists.iter() {
+        for word in words {
+            *occurrences.entry(word).or_insert(0) += 1;
+        }
+    }
+
+    let options = TokenizerOptions::default();
+    let mut hits: HashMap<Language, usize> = HashMap::new();
+    let tokens = messages
+        .iter()
+        .filter_map(|msg| msg.content.as_ref())
+        .flat_map(|content| tokenize(content, &options))
+        .take(DETECTION_SAMPLE_SIZE);
+
+    for token in tokens {
+        for (language, words) in lists.iter() {
+            if let Some(word) = words.get(&token) {
+                if occurrences[word] == 1 {
+                    *hits.entry(*language).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    let max_hits = hits.values().max().cloned().unwrap_or(0);
+    if max_hits == 0 {
+        return Language::ALL.to_vec();
+    }
+
+    let mut languages: Vec<Language> = hits
+        .into_iter()
+        .filter(|(_, count)| *count as f64 >= DETECTION_RELATIVE_THRESHOLD * max_hits as f64)
+        .map(|(language, _)| language)
+        .collect();
+    languages.sort();
+
+    return languages;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect_languages, Language, StopWords};
+    use crate::analysis::fixtures::messages_from;
+
+    #[test]
+    fn test_detect_spanish() {
+        let messages = messages_from(&[
+            ("p1", "Hola, ¿qué tal? Estoy en casa con mi hermano"),
+            ("p1", "Pues nosotros también, hay que ver la película"),
+            ("p1", "Vale, yo estoy aquí desde las ocho"),
+        ]);
+        assert_eq!(detect_languages(&messages), vec![Language::Spanish]);
+
+        let stop_words = StopWords::detect(&messages);
+        assert!(stop_words.contains("que"));
+        assert!(stop_words.contains("de"));
+        assert!(!stop_words.contains("the"));
+    }
+
+    #[test]
+    fn test_detect_french_and_english() {
+        let messages = messages_from(&[
+            ("p1", "Je suis chez moi, tu viens avec nous ce soir ?"),
+            ("p1", "Oui, nous sommes déjà dans le bus"),
+            ("p1", "I think they would like it, we should go there"),
+            ("p1", "You were right about the movie"),
+        ]);
+        assert_eq!(
+            detect_languages(&messages),
+            vec![Language::English, Language::French]
+        );
+    }
+
+    #[test]
+    fn test_detect_nothing() {
+        assert_eq!(detect_languages(&vec![]), Language::ALL.to_vec());
+    }
+
+    #[test]
+    fn test_user_stop_words() {
+        let mut stop_words = StopWords::new(&[Language::English]);
+        assert!(stop_words.contains("don't"));
+        assert!(!stop_words.contains("pizza"));
+
+        stop_words.add_words("# food\nPizza, Pâtes\nburger");
+        assert!(stop_words.contains("pizza"));
+        assert!(stop_words.contains("pâtes"));
+        assert!(stop_words.contains("burger"));
+    }
+}
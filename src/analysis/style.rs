@@ -0,0 +1,252 @@
+use crate::analysis::tokenizer::{tokenize, TokenizerOptions};
+use crate::analysis::unanswered::is_question;
+use crate::parsers::base::BaseMessage;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Type-token ratio at which MTLD closes a factor (McCarthy & Jarvis, 2010).
+const MTLD_THRESHOLD: f64 = 0.72;
+// cSpell: disable
+/// "haha", "hihi", "ahah", "jaja", "lol", "mdr", "ptdr", "xD"... once lowercased.
+const LAUGHTER_PATTERN: &str =
+    r"^(?:(?:ha|he|hi|ah|ja){2,}h?|l+o+l+|lmf?ao+|rofl|mdr+|ptdr+|x+d+)$";
+// cSpell: enable
+const LAUGHTER_EMOJIS: &[&str] = &["😂", "🤣", "😹", "😆"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct WritingStyle {
+    /// Number of messages with some text, the other metrics are computed on them.
+    pub num_texts: i64,
+    /// User perceived characters: an emoji or an accented letter counts once.
+    pub mean_graphemes: f64,
+    pub mean_words: f64,
+    /// Distinct words over words, lower for participants who write more.
+    pub type_token_ratio: f64,
+    /// Measure of textual lexical diversity, does not depend on the amount of text.
+    pub mtld: f64,
+    pub punctuation_per_message: f64,
+    /// Share of the messages with an exclamation mark.
+    pub exclamation_rate: f64,
+    /// Share of the words (of two letters or more) written in capitals.
+    pub caps_rate: f64,
+    /// Share of the messages asking a question, see `is_question`.
+    pub question_rate: f64,
+    /// Share of the messages laughing ("haha", "mdr", "lol", "😂"...).
+    pub laughter_rate: f64,
+    pub words_per_sentence: f64,
+}
+
+impl WritingStyle {
+    /// Label and formatted value of every metric, in the order of the comparison tables.
+    pub fn metrics(&self) -> Vec<(&'static str, String)> {
+        return vec![
+            ("Texts", self.num_texts.to_string()),
+            (
+                "Characters per message",
+                format!("{:.1}", self.mean_graphemes),
+            ),
+            ("Words per message", format!("{:.1}", self.mean_words)),
+            (
+                "Words per sentence",
+                format!("{:.1}", self.words_per_sentence),
+            ),
+            ("Type-token ratio", format!("{:.3}", self.type_token_ratio)),
+            ("MTLD", format!("{:.1}", self.mtld)),
+            (
+                "Punctuation per message",
+                format!("{:.2}", self.punctuation_per_message),
+            ),
+            (
+                "Exclamations",
+                format!("{:.1}%", self.exclamation_rate * 100.0),
+            ),
+            (
+                "Words in capitals",
+                format!("{:.1}%", self.caps_rate * 100.0),
+            ),
+            ("Questions", format!("{:.1}%", self.question_rate * 100.0)),
+            ("Laughter", format!("{:.1}%", self.laughter_rate * 100.0)),
+        ];
+    }
+}
+
+fn mtld_pass<'a>(words: impl Iterator<Item = &'a String>) -> f64 {
+    let mut num_words = 0;
+    let mut factors = 0.0;
+    let mut types: HashSet<&String> = HashSet::new();
+    let mut factor_length = 0;
+
+    for word in words {
+        num_words += 1;
+        factor_length += 1;
+        types.insert(word);
+        if types.len() as f64 / factor_length as f64 <= MTLD_THRESHOLD {
+            factors += 1.0;
+            types.clear();
+            factor_length = 0;
+        }
+    }
+    // Partial factor for the remaining words.
+    if factor_length > 0 {
+        let ratio = types.len() as f64 / factor_length as f64;
+        factors += (1.0 - ratio) / (1.0 - MTLD_THRESHOLD);
+    }
+
+    if factors == 0.0 {
+        return num_words as f64;
+    }
+    return num_words as f64 / factors;
+}
+
+/// MTLD: mean length of the word sequences keeping a type-token ratio above 0.72,
+/// averaged over a forward and a backward pass.
+pub fn get_mtld(words: &[String]) -> f64 {
+    return (mtld_pass(words.iter()) + mtld_pass(words.iter().rev())) / 2.0;
+}
+
+#[derive(Default)]
+struct StyleCounts {
+    texts: i64,
+    graphemes: i64,
+    punctuation: i64,
+    exclamations: i64,
+    caps_words: i64,
+    long_words: i64,
+    questions: i64,
+    laughs: i64,
+    sentences: i64,
+    words: Vec<String>,
+}
+
+/// Writing style of each participant, from the messages with some text.
+pub fn get_writing_styles(messages: &Vec<BaseMessage>) -> HashMap<String, WritingStyle> {
+    let laughter = Regex::new(LAUGHTER_PATTERN).expect("Invalid laughter pattern");
+    let tokenizer_options = TokenizerOptions {
+        keep_numbers: true,
+        ..TokenizerOptions::default()
+    };
+    let mut counts: HashMap<String, StyleCounts> = HashMap::new();
+
+    for msg in messages {
+        let content = match &msg.content {
+            Some(content) if !content.trim().is_empty() => content,
+            _ => continue,
+        };
+        let words = tokenize(content, &tokenizer_options);
+        let counts = counts.entry(msg.sender_name.clone()).or_default();
+
+        counts.texts += 1;
+        counts.graphemes += content.graphemes(true).count() as i64;
+        counts.punctuation += content.chars().filter(|c| c.is_ascii_punctuation()).count() as i64;
+        counts.exclamations += if content.contains('!') { 1 } else { 0 };
+        counts.questions += if is_question(content) { 1 } else { 0 };
+        counts.laughs += if words.iter().any(|word| laughter.is_match(word))
+            || LAUGHTER_EMOJIS.iter().any(|emoji| content.contains(emoji))
+        {
+            1
+        } else {
+            0
+        };
+
+        for word in content.unicode_words() {
+            if word.chars().filter(|c| c.is_alphabetic()).count() < 2 {
+                continue;
+            }
+            counts.long_words += 1;
+            if word.chars().all(|c| !c.is_lowercase()) {
+                counts.caps_words += 1;
+            }
+        }
+        counts.sentences += content
+            .split(['.', '!', '?', '…', '\n'])
+            .filter(|sentence| sentence.unicode_words().next().is_some())
+            .count() as i64;
+        counts.words.extend(words);
+    }
+
+    return counts
+        .into_iter()
+        .map(|(name, counts)| {
+            let texts = counts.texts as f64;
+            let num_words = counts.words.len() as f64;
+            let distinct_words = counts.words.iter().collect::<HashSet<&String>>().len();
+            let ratio = |count: i64, total: f64| {
+                if total > 0.0 {
+                    count as f64 / total
+                } else {
+                    0.0
+                }
+            };
+
+            let style = WritingStyle {
+                num_texts: counts.texts,
+                mean_graphemes: ratio(counts.graphemes, texts),
+                mean_words: num_words / texts,
+                type_token_ratio: ratio(distinct_words as i64, num_words),
+                mtld: get_mtld(&counts.words),
+                punctuation_per_message: ratio(counts.punctuation, texts),
+                exclamation_rate: ratio(counts.exclamations, texts),
+                caps_rate: ratio(counts.caps_words, counts.long_words as f64),
+                question_rate: ratio(counts.questions, texts),
+                laughter_rate: ratio(counts.laughs, texts),
+                words_per_sentence: ratio(counts.words.len() as i64, counts.sentences as f64),
+            };
+            (name, style)
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_mtld, get_writing_styles};
+    use crate::analysis::fixtures::message;
+    use crate::parsers::base::BaseMessage;
+
+    #[test]
+    fn test_mtld() {
+        let repeated: Vec<String> = "a b a b a b a b a b".split(' ').map(String::from).collect();
+        let varied: Vec<String> = "the quick brown fox jumps over one lazy dog again"
+            .split(' ')
+            .map(String::from)
+            .collect();
+        assert!(get_mtld(&repeated) < get_mtld(&varied));
+        // No factor is ever closed: the whole text is one long factor.
+        assert_eq!(get_mtld(&varied), 10.0);
+    }
+
+    #[test]
+    fn test_writing_styles() {
+        let messages: Vec<BaseMessage> = [
+            ("p1", Some("Déjà là 😂")),
+            ("p1", Some("C'est GÉNIAL! Vraiment. On y va?")),
+            ("p1", None),
+            ("p2", Some("hahaha ok")),
+            ("p2", Some("mdr")),
+        ]
+        .iter()
+        .map(|(sender, content)| message(sender, 0, *content))
+        .collect();
+
+        let styles = get_writing_styles(&messages);
+
+        let p1 = &styles["p1"];
+        assert_eq!(p1.num_texts, 2);
+        // "Déjà là 😂" is 9 graphemes (but 15 bytes), the second message 32.
+        assert_eq!(p1.mean_graphemes, 20.5);
+        assert_eq!(p1.exclamation_rate, 0.5);
+        assert_eq!(p1.question_rate, 0.5);
+        assert_eq!(p1.laughter_rate, 0.5);
+        // "GÉNIAL" out of "Déjà", "là", "C'est", "GÉNIAL", "Vraiment", "On", "va".
+        assert_eq!(p1.caps_rate, 1.0 / 7.0);
+        // 2 + 6 words ("c'est" is split into "est"), over 4 sentences.
+        assert_eq!(p1.words_per_sentence, 2.0);
+
+        let p2 = &styles["p2"];
+        assert_eq!(p2.laughter_rate, 1.0);
+        assert_eq!(p2.exclamation_rate, 0.0);
+        assert_eq!(p2.mean_words, 1.5);
+        assert_eq!(p2.type_token_ratio, 1.0);
+    }
+}
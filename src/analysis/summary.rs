@@ -0,0 +1,258 @@
+use crate::analysis::stopwords::StopWords;
+use crate::analysis::timeline::{get_buckets, Granularity};
+use crate::analysis::tokenizer::{tokenize, TokenizerOptions};
+use crate::parsers::base::BaseMessage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+pub const DEFAULT_SUMMARY_MESSAGES: usize = 5;
+/// Texts with less content words say too little to summarise anything.
+const MIN_WORDS: usize = 3;
+/// Only the wordiest texts of a period are ranked, to keep the similarity graph small.
+const MAX_CANDIDATES: usize = 500;
+/// A message this similar to an already picked one repeats it.
+const REDUNDANCY_THRESHOLD: f64 = 0.5;
+const DAMPING: f64 = 0.85;
+const TEXTRANK_ITERATIONS: usize = 30;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SummaryMessage {
+    pub sender_name: String,
+    pub timestamp_ms: i64,
+    pub content: String,
+    /// TextRank of the message among the texts of its period.
+    pub score: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PeriodSummary {
+    /// First day of the period (%Y-%m-%d), like the timeline buckets.
+    pub period: String,
+    pub num_messages: i64,
+    /// Most representative messages, chronologically sorted.
+    pub messages: Vec<SummaryMessage>,
+}
+
+/// Cosine similarity of two sets of words.
+fn get_similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let common = a.intersection(b).count() as f64;
+    return common / ((a.len() * b.len()) as f64).sqrt();
+}
+
+/// PageRank over the weighted similarity graph of the texts.
+fn get_textrank(weights: &Vec<Vec<f64>>) -> Vec<f64> {
+    let num_nodes = weights.len();
+    let out_weights: Vec<f64> = weights.iter().map(|row| row.iter().sum()).collect();
+    let mut ranks = vec![1.0 / num_nodes as f64; num_nodes];
+
+    for _ in 0..TEXTRANK_ITERATIONS {
+        // Texts similar to no other spread their rank over everyone.
+        let dangling: f64 = (0..num_nodes)
+            .filter(|idx| out_weights[*idx] == 0.0)
+            .map(|idx| ranks[idx])
+            .sum();
+        let base = (1.0 - DAMPING) / num_nodes as f64 + DAMPING * dangling / num_nodes as f64;
+        ranks = (0..num_nodes)
+            .map(|target| {
+                base + DAMPING
+                    * (0..num_nodes)
+                        .filter(|source| out_weights[*source] > 0.0)
+                        .map(|source| ranks[source] * weights[source][target] / out_weights[source])
+                        .sum::<f64>()
+            })
+            .collect();
+    }
+    return ranks;
+}
+
+/// Extractive summary: the `num_messages` texts most similar to the rest of the texts
+/// (TextRank), skipping the ones repeating an already picked message.
+pub fn summarize<'a>(
+    messages: impl Iterator<Item = &'a BaseMessage>,
+    stop_words: &StopWords,
+    num_messages: usize,
+) -> Vec<SummaryMessage> {
+    let tokenizer_options = TokenizerOptions::default();
+    let mut candidates: Vec<(&BaseMessage, HashSet<String>)> = messages
+        .filter_map(|msg| {
+            let words: HashSet<String> = tokenize(msg.content.as_deref()?, &tokenizer_options)
+                .into_iter()
+                .filter(|word| !stop_words.contains(word))
+                .collect();
+            if words.len() < MIN_WORDS {
+                return None;
+            }
+            return Some((msg, words));
+        })
+        .collect();
+    if candidates.is_empty() {
+        return vec![];
+    }
+    if candidates.len() > MAX_CANDIDATES {
+        candidates.sort_by_key(|(_, words)| std::cmp::Reverse(words.len()));
+        candidates.truncate(MAX_CANDIDATES);
+        candidates.sort_by_key(|(msg, _)| msg.timestamp_ms);
+    }
+
+    let weights: Vec<Vec<f64>> = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, (_, words))| {
+            candidates
+                .iter()
+                .enumerate()
+                .map(|(j, (_, other))| {
+                    if i == j {
+                        0.0
+                    } else {
+                        get_similarity(words, other)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let ranks = get_textrank(&weights);
+
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_by(|a, b| ranks[*b].total_cmp(&ranks[*a]).then_with(|| a.cmp(b)));
+    let mut picked: Vec<usize> = Vec::new();
+    for idx in order {
+        if picked.len() >= num_messages {
+            break;
+        }
+        if picked
+            .iter()
+            .all(|other| weights[idx][*other] < REDUNDANCY_THRESHOLD)
+        {
+            picked.push(idx);
+        }
+    }
+    picked.sort();
+
+    return picked
+        .into_iter()
+        .map(|idx| {
+            let msg = candidates[idx].0;
+            SummaryMessage {
+                sender_name: msg.sender_name.clone(),
+                timestamp_ms: msg.timestamp_ms,
+                content: msg.content.clone().unwrap_or_default(),
+                score: ranks[idx],
+            }
+        })
+        .collect();
+}
+
+/// Summary of every period with messages, for chronologically sorted messages.
+pub fn summarize_periods(
+    messages: &Vec<BaseMessage>,
+    granularity: Granularity,
+    stop_words: &StopWords,
+    num_messages: usize,
+) -> Vec<PeriodSummary> {
+    let (buckets, indices) = get_buckets(messages, granularity);
+    let mut periods: Vec<Vec<&BaseMessage>> = vec![vec![]; buckets.len()];
+    for (msg, idx) in messages.iter().zip(indices) {
+        if let Some(idx) = idx {
+            periods[idx].push(msg);
+        }
+    }
+
+    return buckets
+        .into_iter()
+        .zip(periods)
+        .filter(|(_, period_messages)| !period_messages.is_empty())
+        .map(|(period, period_messages)| PeriodSummary {
+            period,
+            num_messages: period_messages.len() as i64,
+            messages: summarize(period_messages.into_iter(), stop_words, num_messages),
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{summarize, summarize_periods};
+    use crate::analysis::fixtures::{message, timestamp};
+    use crate::analysis::stopwords::{Language, StopWords};
+    use crate::analysis::timeline::Granularity;
+    use crate::parsers::base::BaseMessage;
+
+    fn messages() -> Vec<BaseMessage> {
+        return [
+            (
+                "p1",
+                "2022-03-01T10:00:00Z",
+                "we should book the train tickets for paris",
+            ),
+            (
+                "p2",
+                "2022-03-01T10:01:00Z",
+                "the train tickets for paris are cheap today",
+            ),
+            ("p1", "2022-03-02T09:00:00Z", "ok"),
+            (
+                "p2",
+                "2022-03-03T18:00:00Z",
+                "my cat is sleeping on the sofa",
+            ),
+            (
+                "p1",
+                "2022-03-04T08:00:00Z",
+                "paris train tickets booked for friday",
+            ),
+            (
+                "p2",
+                "2022-03-04T08:05:00Z",
+                "the train tickets for paris are cheap today",
+            ),
+            (
+                "p1",
+                "2022-05-10T12:00:00Z",
+                "happy birthday, have a wonderful day",
+            ),
+        ]
+        .iter()
+        .map(|(sender, date, content)| message(sender, timestamp(date), Some(content)))
+        .collect();
+    }
+
+    #[test]
+    fn test_summarize() {
+        let messages = messages();
+        let stop_words = StopWords::new(&[Language::English]);
+
+        let summary = summarize(messages.iter().take(6), &stop_words, 3);
+
+        // The train messages repeat each other: only the most central one is kept, "ok" is
+        // too short.
+        let contents: Vec<&str> = summary.iter().map(|msg| msg.content.as_str()).collect();
+        assert_eq!(contents.len(), 2);
+        assert!(contents[0].contains("train"));
+        assert_eq!(contents[1], "my cat is sleeping on the sofa");
+        assert!(summary[0].score > summary[1].score);
+        assert!(summarize(messages.iter().skip(2).take(1), &stop_words, 3).is_empty());
+    }
+
+    #[test]
+    fn test_summarize_periods() {
+        let messages = messages();
+        let stop_words = StopWords::new(&[Language::English]);
+
+        let summaries = summarize_periods(&messages, Granularity::Month, &stop_words, 1);
+
+        // April has no message.
+        let periods: Vec<&str> = summaries
+            .iter()
+            .map(|summary| summary.period.as_str())
+            .collect();
+        assert_eq!(periods, vec!["2022-03-01", "2022-05-01"]);
+        assert_eq!(summaries[0].num_messages, 6);
+        assert_eq!(summaries[0].messages.len(), 1);
+        assert!(summaries[0].messages[0].content.contains("train"));
+        assert_eq!(summaries[1].messages[0].sender_name, "p1");
+    }
+}
@@ -0,0 +1,179 @@
+use crate::parsers::base::BaseMessage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Waiting longer than this (24 hours) for a reply is not a late reply anymore: the
+/// observation is censored there.
+pub const DEFAULT_CENSOR_MS: i64 = 24 * 60 * 60 * 1000;
+/// Normal quantile of the 95% confidence bands.
+const Z_95: f64 = 1.96;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SurvivalPoint {
+    /// Seconds since the message was sent.
+    pub time_s: f64,
+    /// Probability that a message is still unanswered after `time_s`.
+    pub survival: f64,
+    /// 95% confidence band (Greenwood variance, log-log transform).
+    pub lower: f64,
+    pub upper: f64,
+    pub at_risk: usize,
+    pub events: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SurvivalCurve {
+    /// Kaplan–Meier estimate, one point per distinct reply time.
+    pub points: Vec<SurvivalPoint>,
+    /// Time (in seconds) after which half of the messages got a reply, if it happens
+    /// before the censoring.
+    pub median_s: Option<f64>,
+    pub num_events: usize,
+    pub num_censored: usize,
+}
+
+/// Kaplan–Meier estimator of `(time, observed)` pairs, `observed` being false for
+/// censored times.
+pub fn kaplan_meier(observations: &[(f64, bool)]) -> SurvivalCurve {
+    let mut observations = observations.to_vec();
+    observations.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let num_events = observations
+        .iter()
+        .filter(|(_, observed)| *observed)
+        .count();
+    let mut curve = SurvivalCurve {
+        num_events,
+        num_censored: observations.len() - num_events,
+        ..SurvivalCurve::default()
+    };
+
+    let mut survival = 1.0;
+    let mut greenwood_sum = 0.0;
+    let mut idx = 0;
+    while idx < observations.len() {
+        let time = observations[idx].0;
+        let at_risk = observations.len() - idx;
+        let mut events = 0;
+        while idx < observations.len() && observations[idx].0 == time {
+            if observations[idx].1 {
+                events += 1;
+            }
+            idx += 1;
+        }
+        if events == 0 {
+            continue;
+        }
+
+        survival *= 1.0 - events as f64 / at_risk as f64;
+        if events < at_risk {
+            greenwood_sum += events as f64 / (at_risk * (at_risk - events)) as f64;
+        }
+
+        let (lower, upper) = if survival > 0.0 && survival < 1.0 {
+            let log_survival = survival.ln();
+            let margin = Z_95 * greenwood_sum.sqrt() / log_survival.abs();
+            (survival.powf(margin.exp()), survival.powf((-margin).exp()))
+        } else {
+            (survival, survival)
+        };
+
+        curve.points.push(SurvivalPoint {
+            time_s: time,
+            survival,
+            lower,
+            upper,
+            at_risk,
+            events,
+        });
+        if curve.median_s.is_none() && survival <= 0.5 {
+            curve.median_s = Some(time);
+        }
+    }
+
+    return curve;
+}
+
+/// How long the messages of each participant stay unanswered. Only the last message of a
+/// run waits for a reply; it is censored when nobody replies within `censor_ms` or when
+/// the conversation ends first.
+pub fn get_reply_survival(
+    messages: &Vec<BaseMessage>,
+    censor_ms: i64,
+) -> HashMap<String, SurvivalCurve> {
+    let mut observations: HashMap<String, Vec<(f64, bool)>> = HashMap::new();
+    let last_timestamp = messages.last().map(|msg| msg.timestamp_ms).unwrap_or(0);
+
+    let mut messages_iter = messages.iter().peekable();
+    while let Some(msg) = messages_iter.next() {
+        let observation = match messages_iter.peek() {
+            Some(next_msg) if next_msg.sender_name == msg.sender_name => continue,
+            Some(next_msg) => {
+                let delta = next_msg.timestamp_ms - msg.timestamp_ms;
+                (delta.min(censor_ms), delta <= censor_ms)
+            }
+            None => ((last_timestamp - msg.timestamp_ms).min(censor_ms), false),
+        };
+
+        observations
+            .entry(msg.sender_name.clone())
+            .or_default()
+            .push((observation.0 as f64 / 1000.0, observation.1));
+    }
+
+    return observations
+        .into_iter()
+        .map(|(name, observations)| (name, kaplan_meier(&observations)))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_reply_survival, kaplan_meier};
+    use crate::analysis::fixtures::message;
+    use crate::parsers::base::BaseMessage;
+
+    #[test]
+    fn test_kaplan_meier() {
+        let curve = kaplan_meier(&[(20.0, true), (10.0, true), (30.0, false), (40.0, true)]);
+
+        let survival: Vec<(f64, f64)> = curve
+            .points
+            .iter()
+            .map(|point| (point.time_s, point.survival))
+            .collect();
+        assert_eq!(survival, vec![(10.0, 0.75), (20.0, 0.5), (40.0, 0.0)]);
+        assert_eq!(curve.median_s, Some(20.0));
+        assert_eq!(curve.num_events, 3);
+        assert_eq!(curve.num_censored, 1);
+        assert_eq!(curve.points[1].at_risk, 3);
+
+        let point = &curve.points[0];
+        assert!(point.lower < point.survival && point.survival < point.upper);
+        assert!(point.lower >= 0.0 && point.upper <= 1.0);
+    }
+
+    #[test]
+    fn test_reply_survival() {
+        let hour = 60 * 60 * 1000;
+        let messages: Vec<BaseMessage> = [
+            ("p1", 0),
+            ("p2", 1000),      // p1 answered after 1s
+            ("p2", 2000),      // p2 run goes on
+            ("p1", 50 * hour), // p2 waited more than a day: censored
+            ("p2", 50 * hour + 10_000),
+        ]
+        .iter()
+        .map(|(sender, timestamp_ms)| message(sender, *timestamp_ms, None))
+        .collect();
+
+        let survival = get_reply_survival(&messages, 24 * hour);
+
+        assert_eq!(survival["p1"].num_events, 2);
+        assert_eq!(survival["p1"].median_s, Some(1.0));
+        // The long silence and the end of the conversation are censored.
+        assert_eq!(survival["p2"].num_events, 0);
+        assert_eq!(survival["p2"].num_censored, 2);
+        assert_eq!(survival["p2"].median_s, None);
+    }
+}
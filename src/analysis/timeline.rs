@@ -0,0 +1,204 @@
+use crate::parsers::base::BaseMessage;
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Granularity {
+    Day,
+    #[default]
+    Week,
+    Month,
+}
+
+impl Granularity {
+    pub const ALL: [Granularity; 3] = [Granularity::Day, Granularity::Week, Granularity::Month];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Granularity::Day => "day",
+            Granularity::Week => "week",
+            Granularity::Month => "month",
+        };
+    }
+
+    pub fn from_name(name: &str) -> Option<Granularity> {
+        return Granularity::ALL
+            .into_iter()
+            .find(|granularity| granularity.name() == name.to_lowercase());
+    }
+
+    /// First day of the bucket containing `date` (weeks start on Monday).
+    fn bucket_start(&self, date: NaiveDate) -> NaiveDate {
+        return match self {
+            Granularity::Day => date,
+            Granularity::Week => {
+                date - Duration::days(date.weekday().num_days_from_monday() as i64)
+            }
+            Granularity::Month => date.with_day(1).expect("Every month has a first day"),
+        };
+    }
+
+    fn next_bucket(&self, start: NaiveDate) -> NaiveDate {
+        return match self {
+            Granularity::Day => start + Duration::days(1),
+            Granularity::Week => start + Duration::days(7),
+            Granularity::Month => start
+                .checked_add_months(Months::new(1))
+                .expect("Date out of range"),
+        };
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Timeline {
+    pub granularity: Granularity,
+    /// Number of buckets averaged in `rolling_average`.
+    pub window: usize,
+    /// First day (%Y-%m-%d) of every bucket from the first to the last message,
+    /// including the buckets without any message.
+    pub buckets: Vec<String>,
+    /// Number of messages of each participant in each bucket.
+    pub counts: HashMap<String, Vec<i64>>,
+    /// Trailing rolling average of `counts`, over `window` buckets.
+    pub rolling_average: HashMap<String, Vec<f64>>,
+}
+
+/// Date and time (UTC) of a message, "%Y-%m-%d %H:%M", empty when out of range.
+pub fn format_timestamp(timestamp_ms: i64) -> String {
+    return DateTime::from_timestamp_millis(timestamp_ms)
+        .map(|datetime| datetime.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+}
+
+/// Trailing mean over `window` values, the first values are averaged over what is available.
+pub fn get_rolling_average(values: &[i64], window: usize) -> Vec<f64> {
+    let window = window.max(1);
+    let mut averages: Vec<f64> = Vec::with_capacity(values.len());
+    let mut sum = 0;
+
+    for (idx, value) in values.iter().enumerate() {
+        sum += value;
+        if idx >= window {
+            sum -= values[idx - window];
+        }
+        averages.push(sum as f64 / (idx + 1).min(window) as f64);
+    }
+
+    return averages;
+}
+
+/// First day (%Y-%m-%d) of every bucket from the first to the last message, and the
+/// index of the bucket of each message (`None` for invalid timestamps).
+pub fn get_buckets(
+    messages: &Vec<BaseMessage>,
+    granularity: Granularity,
+) -> (Vec<String>, Vec<Option<usize>>) {
+    let dates: Vec<Option<NaiveDate>> = messages
+        .iter()
+        .map(|msg| {
+            DateTime::from_timestamp_millis(msg.timestamp_ms)
+                .map(|datetime| granularity.bucket_start(datetime.date_naive()))
+        })
+        .collect();
+
+    let (first, last) = match (dates.iter().flatten().min(), dates.iter().flatten().max()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return (vec![], vec![None; messages.len()]),
+    };
+
+    let mut buckets: Vec<String> = Vec::new();
+    let mut bucket_indices: HashMap<NaiveDate, usize> = HashMap::new();
+    let mut bucket = first;
+    while bucket <= last {
+        bucket_indices.insert(bucket, buckets.len());
+        buckets.push(bucket.format("%Y-%m-%d").to_string());
+        bucket = granularity.next_bucket(bucket);
+    }
+
+    let indices = dates
+        .iter()
+        .map(|date| date.map(|date| bucket_indices[&date]))
+        .collect();
+    return (buckets, indices);
+}
+
+/// Number of messages per participant per day, week or month, for chronologically
+/// sorted messages.
+pub fn get_timeline(
+    messages: &Vec<BaseMessage>,
+    granularity: Granularity,
+    window: usize,
+) -> Timeline {
+    let (buckets, indices) = get_buckets(messages, granularity);
+    let num_buckets = buckets.len();
+    let mut timeline = Timeline {
+        granularity,
+        window,
+        buckets,
+        ..Timeline::default()
+    };
+
+    for (msg, idx) in messages.iter().zip(indices.iter()) {
+        if let Some(idx) = idx {
+            timeline
+                .counts
+                .entry(msg.sender_name.clone())
+                .or_insert_with(|| vec![0; num_buckets])[*idx] += 1;
+        }
+    }
+
+    timeline.rolling_average = timeline
+        .counts
+        .iter()
+        .map(|(name, counts)| (name.clone(), get_rolling_average(counts, window)))
+        .collect();
+
+    return timeline;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_rolling_average, get_timeline, Granularity};
+    use crate::analysis::fixtures::{message, timestamp};
+
+    #[test]
+    fn test_rolling_average() {
+        assert_eq!(
+            get_rolling_average(&[2, 4, 6, 0], 2),
+            vec![2.0, 3.0, 5.0, 3.0]
+        );
+        assert_eq!(get_rolling_average(&[1, 2], 0), vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_timeline() {
+        let messages = vec![
+            message("p1", timestamp("2024-01-29T10:00:00Z"), None), // Monday
+            message("p2", timestamp("2024-02-01T10:00:00Z"), None),
+            message("p1", timestamp("2024-02-04T23:00:00Z"), None), // Sunday, same week
+            message("p1", timestamp("2024-02-20T10:00:00Z"), None),
+        ];
+
+        let weeks = get_timeline(&messages, Granularity::Week, 2);
+        assert_eq!(
+            weeks.buckets,
+            vec!["2024-01-29", "2024-02-05", "2024-02-12", "2024-02-19"]
+        );
+        assert_eq!(weeks.counts["p1"], vec![2, 0, 0, 1]);
+        assert_eq!(weeks.counts["p2"], vec![1, 0, 0, 0]);
+        assert_eq!(weeks.rolling_average["p1"], vec![2.0, 1.0, 0.0, 0.5]);
+
+        let months = get_timeline(&messages, Granularity::Month, 1);
+        assert_eq!(months.buckets, vec!["2024-01-01", "2024-02-01"]);
+        assert_eq!(months.counts["p1"], vec![1, 2]);
+
+        let days = get_timeline(&messages, Granularity::Day, 7);
+        assert_eq!(days.buckets.len(), 23);
+
+        assert_eq!(Granularity::from_name("Month"), Some(Granularity::Month));
+        assert!(get_timeline(&vec![], Granularity::Day, 7)
+            .buckets
+            .is_empty());
+    }
+}
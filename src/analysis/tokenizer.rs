@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
+
+// cSpell: disable
+/// French elided articles and pronouns: "j'ai" => "ai", "l'homme" => "homme".
+const ELISIONS: &[&str] = &[
+    "c", "d", "j", "l", "m", "n", "s", "t", "qu", "jusqu", "lorsqu", "puisqu", "quoiqu",
+];
+// cSpell: enable
+
+const APOSTROPHES: &[char] = &['\'', '’'];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TokenizerOptions {
+    /// Case fold every token.
+    pub lowercase: bool,
+    /// Remove diacritics ("été" => "ete"), tokens are always NFC normalised.
+    pub strip_accents: bool,
+    /// Drop French elisions ("j'ai" => "ai").
+    pub split_elisions: bool,
+    pub keep_urls: bool,
+    /// Keep @mentions.
+    pub keep_mentions: bool,
+    pub keep_numbers: bool,
+}
+
+impl Default for TokenizerOptions {
+    fn default() -> Self {
+        return TokenizerOptions {
+            lowercase: true,
+            strip_accents: false,
+            split_elisions: true,
+            keep_urls: false,
+            keep_mentions: false,
+            keep_numbers: false,
+        };
+    }
+}
+
+pub fn is_url(chunk: &str) -> bool {
+    let chunk = chunk.to_lowercase();
+    return chunk.starts_with("http://")
+        || chunk.starts_with("https://")
+        || chunk.starts_with("www.");
+}
+
+fn is_mention(chunk: &str) -> bool {
+    return chunk.len() > 1 && chunk.starts_with('@');
+}
+
+fn is_number(word: &str) -> bool {
+    return word.chars().all(|c| c.is_numeric() || c == '.' || c == ',');
+}
+
+/// Elided prefix and word of a French elision: "j'ai" => ("j", "ai").
+pub fn split_elision(word: &str) -> Option<(&str, &str)> {
+    let idx = word.find(APOSTROPHES)?;
+    let (prefix, rest) = word.split_at(idx);
+    let rest = &rest[rest.chars().next()?.len_utf8()..];
+    if ELISIONS.contains(&prefix.to_lowercase().as_str()) && !rest.is_empty() {
+        return Some((prefix, rest));
+    }
+    return None;
+}
+
+fn normalize_word(word: &str, options: &TokenizerOptions) -> String {
+    let mut word: String = word.nfc().collect();
+
+    if options.lowercase {
+        word = word.to_lowercase();
+    }
+
+    if options.split_elisions {
+        if let Some((_, rest)) = split_elision(&word) {
+            word = rest.to_string();
+        }
+    }
+
+    if options.strip_accents {
+        word = word
+            .nfd()
+            .filter(|c| !is_combining_mark(*c))
+            .nfc()
+            .collect();
+    }
+
+    return word.replace('’', "'");
+}
+
+/// Split a message into word tokens, using Unicode word boundaries (punctuation and emojis
+/// are never part of a token). URLs and @mentions are detected on whitespace separated
+/// chunks beforehand so they are either kept whole or dropped.
+pub fn tokenize(text: &str, options: &TokenizerOptions) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+
+    for chunk in text.split_whitespace() {
+        if is_url(chunk) || is_mention(chunk) {
+            let keep = if is_url(chunk) {
+                options.keep_urls
+            } else {
+                options.keep_mentions
+            };
+            if keep {
+                let chunk = chunk.trim_end_matches(|c: char| c.is_ascii_punctuation() && c != '/');
+                tokens.push(if options.lowercase {
+                    chunk.to_lowercase()
+                } else {
+                    chunk.to_string()
+                });
+            }
+            continue;
+        }
+
+        for word in chunk.unicode_words() {
+            if !options.keep_numbers && is_number(word) {
+                continue;
+            }
+            let word = normalize_word(word, options);
+            if !word.is_empty() {
+                tokens.push(word);
+            }
+        }
+    }
+
+    return tokens;
+}
+
+/// Consecutive groups of `n` tokens, joined by a space.
+pub fn get_ngrams(tokens: &[String], n: usize) -> Vec<String> {
+    if n == 0 || tokens.len() < n {
+        return Vec::new();
+    }
+    return tokens.windows(n).map(|window| window.join(" ")).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_ngrams, tokenize, TokenizerOptions};
+
+    #[test]
+    fn test_tokenize_english() {
+        let options = TokenizerOptions::default();
+        assert_eq!(
+            tokenize("Hello! hello, HELLO... don't stop 😂", &options),
+            vec!["hello", "hello", "hello", "don't", "stop"]
+        );
+        assert_eq!(
+            tokenize(
+                "see https://example.com/a?b=c, @bob at 10:30 or 42 times",
+                &options
+            ),
+            vec!["see", "at", "or", "times"]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_french() {
+        let options = TokenizerOptions::default();
+        assert_eq!(
+            tokenize("Salut! J'ai vu l’homme qu'il aime, c'est génial", &options),
+            vec!["salut", "ai", "vu", "homme", "il", "aime", "est", "génial"]
+        );
+        // Decomposed and composed accents are the same token.
+        assert_eq!(
+            tokenize("e\u{301}te\u{301} été", &options),
+            vec!["été", "été"]
+        );
+        assert_eq!(
+            tokenize(
+                "Été à la plage",
+                &TokenizerOptions {
+                    strip_accents: true,
+                    ..TokenizerOptions::default()
+                }
+            ),
+            vec!["ete", "a", "la", "plage"]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_keep_options() {
+        let options = TokenizerOptions {
+            lowercase: false,
+            split_elisions: false,
+            keep_urls: true,
+            keep_mentions: true,
+            keep_numbers: true,
+            ..TokenizerOptions::default()
+        };
+        assert_eq!(
+            tokenize("Va sur www.site.fr, @Bob: j'ai 2 places.", &options),
+            vec!["Va", "sur", "www.site.fr", "@Bob", "j'ai", "2", "places"]
+        );
+    }
+
+    #[test]
+    fn test_ngrams() {
+        let tokens = tokenize("on se voit demain", &TokenizerOptions::default());
+        assert_eq!(
+            get_ngrams(&tokens, 2),
+            vec!["on se", "se voit", "voit demain"]
+        );
+        assert_eq!(get_ngrams(&tokens, 4), vec!["on se voit demain"]);
+        assert!(get_ngrams(&tokens, 5).is_empty());
+    }
+}
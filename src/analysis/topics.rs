@@ -0,0 +1,348 @@
+use crate::analysis::random::Rng;
+use crate::analysis::sessions::{get_sessions, DEFAULT_SESSION_GAP_MS};
+use crate::analysis::stopwords::StopWords;
+use crate::analysis::tokenizer::{tokenize, TokenizerOptions};
+use crate::parsers::base::BaseMessage;
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Words used in fewer documents than this are left out of the vocabulary.
+const MIN_DOCUMENT_FREQUENCY: usize = 2;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TopicOptions {
+    pub num_topics: usize,
+    /// Number of Gibbs sampling sweeps over the whole conversation.
+    pub num_iterations: usize,
+    /// Dirichlet prior of the topics of a document.
+    pub alpha: f64,
+    /// Dirichlet prior of the words of a topic.
+    pub beta: f64,
+    /// Number of terms kept to describe each topic.
+    pub num_terms: usize,
+    /// Pause between two messages starting a new document (session).
+    pub gap_ms: i64,
+    /// Same seed and same messages give the same topics.
+    pub seed: u64,
+}
+
+impl Default for TopicOptions {
+    fn default() -> Self {
+        return TopicOptions {
+            num_topics: 6,
+            num_iterations: 100,
+            alpha: 0.1,
+            beta: 0.01,
+            num_terms: 10,
+            gap_ms: DEFAULT_SESSION_GAP_MS,
+            seed: 42,
+        };
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Topics {
+    /// Most probable terms of each topic, with their probability.
+    pub terms: Vec<Vec<(String, f64)>>,
+    /// Share of each topic in the words of each participant.
+    pub per_participant: HashMap<String, Vec<f64>>,
+    /// Share of each topic per month (%Y-%m), over the whole conversation.
+    pub over_time: BTreeMap<String, Vec<f64>>,
+    /// Share of each topic per month (%Y-%m), per participant.
+    pub over_time_per_participant: HashMap<String, BTreeMap<String, Vec<f64>>>,
+}
+
+impl Topics {
+    /// Short name of a topic: its three most probable terms.
+    pub fn label(&self, topic: usize) -> String {
+        return self.terms[topic]
+            .iter()
+            .take(3)
+            .map(|(term, _)| term.as_str())
+            .collect::<Vec<&str>>()
+            .join(", ");
+    }
+}
+
+/// A word of a document: its index in the vocabulary and the participant who wrote it.
+struct Token {
+    word: usize,
+    sender: usize,
+}
+
+struct Document {
+    month: String,
+    tokens: Vec<Token>,
+}
+
+fn normalize(counts: &[f64]) -> Vec<f64> {
+    let total: f64 = counts.iter().sum();
+    if total == 0.0 {
+        return vec![0.0; counts.len()];
+    }
+    return counts.iter().map(|count| count / total).collect();
+}
+
+/// Latent Dirichlet Allocation fitted with collapsed Gibbs sampling. Each conversation
+/// session is a document, stop words and rare words are left out.
+pub fn get_topics(
+    messages: &Vec<BaseMessage>,
+    stop_words: &StopWords,
+    options: &TopicOptions,
+) -> Topics {
+    let tokenizer_options = TokenizerOptions::default();
+    let num_topics = options.num_topics.max(1);
+
+    let mut senders: Vec<String> = Vec::new();
+    let mut raw_documents: Vec<(String, Vec<(String, usize)>)> = Vec::new();
+    for session in get_sessions(messages, options.gap_ms) {
+        let month = DateTime::from_timestamp_millis(messages[session.start].timestamp_ms)
+            .map(|datetime| datetime.format("%Y-%m").to_string())
+            .unwrap_or_default();
+        let mut words: Vec<(String, usize)> = Vec::new();
+
+        for msg in messages[session].iter() {
+            let sender = match senders.iter().position(|name| *name == msg.sender_name) {
+                Some(sender) => sender,
+                None => {
+                    senders.push(msg.sender_name.clone());
+                    senders.len() - 1
+                }
+            };
+            if let Some(content) = &msg.content {
+                for word in tokenize(content, &tokenizer_options) {
+                    if !stop_words.contains(&word) {
+                        words.push((word, sender));
+                    }
+                }
+            }
+        }
+        raw_documents.push((month, words));
+    }
+
+    let mut document_frequencies: HashMap<&String, usize> = HashMap::new();
+    for (_, words) in raw_documents.iter() {
+        let mut seen: Vec<&String> = words.iter().map(|(word, _)| word).collect();
+        seen.sort();
+        seen.dedup();
+        for word in seen {
+            *document_frequencies.entry(word).or_insert(0) += 1;
+        }
+    }
+    // Sorted so that word indices, hence the sampling, do not depend on hashing.
+    let mut vocabulary: Vec<String> = document_frequencies
+        .into_iter()
+        .filter(|(_, frequency)| *frequency >= MIN_DOCUMENT_FREQUENCY)
+        .map(|(word, _)| word.clone())
+        .collect();
+    vocabulary.sort();
+    let word_indices: HashMap<&String, usize> = vocabulary
+        .iter()
+        .enumerate()
+        .map(|(idx, word)| (word, idx))
+        .collect();
+
+    let documents: Vec<Document> = raw_documents
+        .iter()
+        .map(|(month, words)| Document {
+            month: month.clone(),
+            tokens: words
+                .iter()
+                .filter_map(|(word, sender)| {
+                    Some(Token {
+                        word: *word_indices.get(word)?,
+                        sender: *sender,
+                    })
+                })
+                .collect(),
+        })
+        .filter(|document| !document.tokens.is_empty())
+        .collect();
+
+    let num_words = vocabulary.len();
+    let mut rng = Rng(options.seed);
+    let mut document_topics = vec![vec![0.0; num_topics]; documents.len()];
+    let mut topic_words = vec![vec![0.0; num_words]; num_topics];
+    let mut topic_totals = vec![0.0; num_topics];
+    let mut assignments: Vec<Vec<usize>> = Vec::new();
+
+    for (d, document) in documents.iter().enumerate() {
+        let mut topics = Vec::new();
+        for token in document.tokens.iter() {
+            let topic = (rng.next_u64() % num_topics as u64) as usize;
+            document_topics[d][topic] += 1.0;
+            topic_words[topic][token.word] += 1.0;
+            topic_totals[topic] += 1.0;
+            topics.push(topic);
+        }
+        assignments.push(topics);
+    }
+
+    let beta_total = options.beta * num_words as f64;
+    let mut probabilities = vec![0.0; num_topics];
+    for _ in 0..options.num_iterations {
+        for (d, document) in documents.iter().enumerate() {
+            for (t, token) in document.tokens.iter().enumerate() {
+                let old_topic = assignments[d][t];
+                document_topics[d][old_topic] -= 1.0;
+                topic_words[old_topic][token.word] -= 1.0;
+                topic_totals[old_topic] -= 1.0;
+
+                let mut total = 0.0;
+                for k in 0..num_topics {
+                    total += (document_topics[d][k] + options.alpha)
+                        * (topic_words[k][token.word] + options.beta)
+                        / (topic_totals[k] + beta_total);
+                    probabilities[k] = total;
+                }
+                let threshold = rng.next_f64() * total;
+                let new_topic = probabilities
+                    .iter()
+                    .position(|cumulative| *cumulative > threshold)
+                    .unwrap_or(num_topics - 1);
+
+                document_topics[d][new_topic] += 1.0;
+                topic_words[new_topic][token.word] += 1.0;
+                topic_totals[new_topic] += 1.0;
+                assignments[d][t] = new_topic;
+            }
+        }
+    }
+
+    let mut terms: Vec<Vec<(String, f64)>> = Vec::new();
+    for k in 0..num_topics {
+        let mut topic_terms: Vec<(String, f64)> = vocabulary
+            .iter()
+            .enumerate()
+            .map(|(w, word)| {
+                (
+                    word.clone(),
+                    (topic_words[k][w] + options.beta) / (topic_totals[k] + beta_total),
+                )
+            })
+            .collect();
+        topic_terms.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        topic_terms.truncate(options.num_terms);
+        terms.push(topic_terms);
+    }
+
+    let mut participant_counts: HashMap<String, Vec<f64>> = HashMap::new();
+    let mut month_counts: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    let mut participant_month_counts: HashMap<String, BTreeMap<String, Vec<f64>>> = HashMap::new();
+    for (d, document) in documents.iter().enumerate() {
+        for (t, token) in document.tokens.iter().enumerate() {
+            let topic = assignments[d][t];
+            let name = &senders[token.sender];
+
+            participant_counts
+                .entry(name.clone())
+                .or_insert_with(|| vec![0.0; num_topics])[topic] += 1.0;
+            month_counts
+                .entry(document.month.clone())
+                .or_insert_with(|| vec![0.0; num_topics])[topic] += 1.0;
+            participant_month_counts
+                .entry(name.clone())
+                .or_default()
+                .entry(document.month.clone())
+                .or_insert_with(|| vec![0.0; num_topics])[topic] += 1.0;
+        }
+    }
+
+    let per_participant = participant_counts
+        .into_iter()
+        .map(|(name, counts)| (name, normalize(&counts)))
+        .collect();
+    let over_time = month_counts
+        .into_iter()
+        .map(|(month, counts)| (month, normalize(&counts)))
+        .collect();
+    let over_time_per_participant = participant_month_counts
+        .into_iter()
+        .map(|(name, months)| {
+            (
+                name,
+                months
+                    .into_iter()
+                    .map(|(month, counts)| (month, normalize(&counts)))
+                    .collect(),
+            )
+        })
+        .collect();
+
+    return Topics {
+        terms,
+        per_participant,
+        over_time,
+        over_time_per_participant,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_topics, TopicOptions};
+    use crate::analysis::fixtures::message;
+    use crate::analysis::stopwords::{Language, StopWords};
+    use crate::parsers::base::BaseMessage;
+
+    fn conversation() -> Vec<BaseMessage> {
+        let day = 24 * 60 * 60 * 1000;
+        let sessions = [
+            ("p1", "pizza pasta dinner restaurant"),
+            ("p2", "football match goal team"),
+            ("p1", "dinner pizza restaurant pasta"),
+            ("p2", "goal team football match"),
+            ("p1", "restaurant pasta pizza dinner"),
+            ("p2", "match goal football team"),
+        ];
+
+        sessions
+            .iter()
+            .enumerate()
+            .map(|(idx, (sender, content))| {
+                message(sender, 1688067261456 + idx as i64 * day, Some(content))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_topics() {
+        let messages = conversation();
+        let stop_words = StopWords::new(&[Language::English]);
+        let options = TopicOptions {
+            num_topics: 2,
+            num_terms: 4,
+            ..TopicOptions::default()
+        };
+        let topics = get_topics(&messages, &stop_words, &options);
+
+        assert_eq!(topics.terms.len(), 2);
+        let mut themes: Vec<Vec<String>> = topics
+            .terms
+            .iter()
+            .map(|terms| {
+                let mut words: Vec<String> = terms.iter().map(|(word, _)| word.clone()).collect();
+                words.sort();
+                words
+            })
+            .collect();
+        themes.sort();
+        assert_eq!(
+            themes,
+            vec![
+                vec!["dinner", "pasta", "pizza", "restaurant"],
+                vec!["football", "goal", "match", "team"],
+            ]
+        );
+
+        // Each participant talks about a single topic.
+        for shares in topics.per_participant.values() {
+            assert!(shares.iter().any(|share| *share > 0.9));
+        }
+        assert_eq!(topics.over_time_per_participant["p1"]["2023-06"].len(), 2);
+
+        // Deterministic given the seed.
+        let again = get_topics(&messages, &stop_words, &options);
+        assert_eq!(topics.terms, again.terms);
+    }
+}
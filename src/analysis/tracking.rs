@@ -0,0 +1,184 @@
+use crate::analysis::timeline::{get_buckets, Granularity};
+use crate::analysis::tokenizer::{tokenize, TokenizerOptions};
+use crate::parsers::base::BaseMessage;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A word or phrase matched on tokens, or a regular expression written between slashes.
+#[derive(Clone, Debug)]
+pub enum Term {
+    Words(Vec<String>),
+    Pattern(Regex),
+}
+
+fn get_tokenizer_options() -> TokenizerOptions {
+    // "cafe" finds "café" and "covid 19" keeps its number.
+    return TokenizerOptions {
+        strip_accents: true,
+        keep_numbers: true,
+        ..TokenizerOptions::default()
+    };
+}
+
+impl Term {
+    /// "/covid(-19)?/" is a case insensitive expression, anything else is tokenized like
+    /// the messages. `None` for invalid expressions and terms without any word.
+    pub fn parse(text: &str) -> Option<Term> {
+        let text = text.trim();
+        if text.len() > 2 && text.starts_with('/') && text.ends_with('/') {
+            return Regex::new(&format!("(?i){}", &text[1..text.len() - 1]))
+                .ok()
+                .map(Term::Pattern);
+        }
+        let words = tokenize(text, &get_tokenizer_options());
+        if words.is_empty() {
+            return None;
+        }
+        return Some(Term::Words(words));
+    }
+
+    fn count(&self, content: &str, tokens: &[String]) -> i64 {
+        return match self {
+            Term::Words(words) => tokens
+                .windows(words.len())
+                .filter(|window| window == words)
+                .count() as i64,
+            Term::Pattern(regex) => regex.find_iter(content).count() as i64,
+        };
+    }
+}
+
+/// A message using a term.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Occurrence {
+    pub sender_name: String,
+    pub timestamp_ms: i64,
+    pub content: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TermUsage {
+    /// The term as it was given.
+    pub term: String,
+    pub total: i64,
+    /// Number of uses by each participant in each bucket of `TermTracking::buckets`.
+    pub counts: HashMap<String, Vec<i64>>,
+    pub first: Option<Occurrence>,
+    pub last: Option<Occurrence>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TermTracking {
+    pub granularity: Granularity,
+    /// Buckets of the whole conversation, like the timeline.
+    pub buckets: Vec<String>,
+    /// One entry per tracked term, in the same order.
+    pub terms: Vec<TermUsage>,
+}
+
+/// When each term was used and by whom, for chronologically sorted messages.
+pub fn track_terms(
+    messages: &Vec<BaseMessage>,
+    terms: &[(String, Term)],
+    granularity: Granularity,
+) -> TermTracking {
+    let (buckets, indices) = get_buckets(messages, granularity);
+    let num_buckets = buckets.len();
+    let tokenizer_options = get_tokenizer_options();
+    let mut usages: Vec<TermUsage> = terms
+        .iter()
+        .map(|(text, _)| TermUsage {
+            term: text.clone(),
+            ..TermUsage::default()
+        })
+        .collect();
+
+    for (msg, idx) in messages.iter().zip(indices.iter()) {
+        let (content, idx) = match (&msg.content, idx) {
+            (Some(content), Some(idx)) => (content, *idx),
+            _ => continue,
+        };
+        let tokens = tokenize(content, &tokenizer_options);
+
+        for ((_, term), usage) in terms.iter().zip(usages.iter_mut()) {
+            let count = term.count(content, &tokens);
+            if count == 0 {
+                continue;
+            }
+            usage.total += count;
+            usage
+                .counts
+                .entry(msg.sender_name.clone())
+                .or_insert_with(|| vec![0; num_buckets])[idx] += count;
+
+            let occurrence = Occurrence {
+                sender_name: msg.sender_name.clone(),
+                timestamp_ms: msg.timestamp_ms,
+                content: content.clone(),
+            };
+            if usage.first.is_none() {
+                usage.first = Some(occurrence.clone());
+            }
+            usage.last = Some(occurrence);
+        }
+    }
+
+    return TermTracking {
+        granularity,
+        buckets,
+        terms: usages,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{track_terms, Term};
+    use crate::analysis::fixtures::{message, timestamp};
+    use crate::analysis::timeline::Granularity;
+    use crate::parsers::base::BaseMessage;
+
+    #[test]
+    fn test_track_terms() {
+        let messages: Vec<BaseMessage> = [
+            ("p1", "2024-01-03T10:00:00Z", "Le café était froid"),
+            (
+                "p2",
+                "2024-01-20T10:00:00Z",
+                "Covid-19 again... covid everywhere",
+            ),
+            ("p1", "2024-03-05T10:00:00Z", "a cafe and the COVID"),
+            ("p2", "2024-03-06T10:00:00Z", "cafeteria"),
+        ]
+        .iter()
+        .map(|(sender, date, content)| message(sender, timestamp(date), Some(content)))
+        .collect();
+        let terms: Vec<(String, Term)> = ["Café", "/covid(-19)?/", "cafe and"]
+            .iter()
+            .map(|text| (text.to_string(), Term::parse(text).unwrap()))
+            .collect();
+
+        let tracking = track_terms(&messages, &terms, Granularity::Month);
+
+        assert_eq!(
+            tracking.buckets,
+            vec!["2024-01-01", "2024-02-01", "2024-03-01"]
+        );
+        let cafe = &tracking.terms[0];
+        assert_eq!(cafe.term, "Café");
+        // "cafeteria" is another word.
+        assert_eq!(cafe.total, 2);
+        assert_eq!(cafe.counts["p1"], vec![1, 0, 1]);
+        assert!(!cafe.counts.contains_key("p2"));
+
+        let covid = &tracking.terms[1];
+        assert_eq!(covid.total, 3);
+        assert_eq!(covid.counts["p2"], vec![2, 0, 0]);
+        assert_eq!(covid.first.as_ref().unwrap().sender_name, "p2");
+        assert_eq!(covid.last.as_ref().unwrap().content, "a cafe and the COVID");
+
+        assert_eq!(tracking.terms[2].total, 1);
+        assert!(Term::parse("/(/").is_none());
+        assert!(Term::parse("  ...").is_none());
+    }
+}
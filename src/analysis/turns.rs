@@ -0,0 +1,120 @@
+use crate::analysis::sessions::get_sessions;
+use crate::parsers::base::BaseMessage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TurnTaking {
+    /// Length of every burst (messages in a row inside a session), per sender.
+    pub runs: HashMap<String, Vec<i64>>,
+    /// Share of the bursts of a sender made of at least two messages.
+    pub double_text_rate: HashMap<String, f64>,
+    /// Share of the bursts of a sender made of at least three messages.
+    pub triple_text_rate: HashMap<String, f64>,
+    /// Average number of turns (bursts) per session.
+    pub turns_per_session: f64,
+}
+
+struct Run {
+    sender: String,
+    length: i64,
+}
+
+fn get_session_runs(session: &[BaseMessage]) -> Vec<Run> {
+    let mut runs: Vec<Run> = Vec::new();
+
+    for msg in session {
+        match runs.last_mut() {
+            Some(run) if run.sender == msg.sender_name => run.length += 1,
+            _ => runs.push(Run {
+                sender: msg.sender_name.clone(),
+                length: 1,
+            }),
+        }
+    }
+
+    return runs;
+}
+
+/// Turn-taking statistics: bursts of messages sent in a row by the same person,
+/// and how often someone double (or triple) texts. Bursts never cross sessions, see
+/// `get_sessions`. Unanswered bursts are counted by `get_unanswered_stats`.
+pub fn get_turn_taking(messages: &Vec<BaseMessage>, gap_ms: i64) -> TurnTaking {
+    let sessions = get_sessions(messages, gap_ms);
+
+    let mut turns = TurnTaking::default();
+    let mut double_texts: HashMap<String, i64> = HashMap::new();
+    let mut triple_texts: HashMap<String, i64> = HashMap::new();
+    let mut num_turns = 0;
+
+    for session in sessions.iter() {
+        let runs = get_session_runs(&messages[session.clone()]);
+        num_turns += runs.len();
+
+        for run in runs {
+            if run.length >= 2 {
+                *double_texts.entry(run.sender.clone()).or_insert(0) += 1;
+            }
+            if run.length >= 3 {
+                *triple_texts.entry(run.sender.clone()).or_insert(0) += 1;
+            }
+            turns.runs.entry(run.sender).or_default().push(run.length);
+        }
+    }
+
+    for (sender, runs) in turns.runs.iter() {
+        let num_runs = runs.len() as f64;
+        let rate =
+            |counts: &HashMap<String, i64>| *counts.get(sender).unwrap_or(&0) as f64 / num_runs;
+
+        turns
+            .double_text_rate
+            .insert(sender.clone(), rate(&double_texts));
+        turns
+            .triple_text_rate
+            .insert(sender.clone(), rate(&triple_texts));
+    }
+
+    if !sessions.is_empty() {
+        turns.turns_per_session = num_turns as f64 / sessions.len() as f64;
+    }
+
+    return turns;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_turn_taking;
+    use crate::analysis::fixtures::message;
+
+    #[test]
+    fn test_turn_taking() {
+        // Session 1: p1 p1 p1 | p2 | p1 p1
+        // Session 2: p2 | p1
+        let messages = vec![
+            message("p1", 0, None),
+            message("p1", 1, None),
+            message("p1", 2, None),
+            message("p2", 3, None),
+            message("p1", 4, None),
+            message("p1", 5, None),
+            message("p2", 1_000, None),
+            message("p1", 1_001, None),
+        ];
+        let turns = get_turn_taking(&messages, 100);
+
+        assert_eq!(turns.runs["p1"], vec![3, 2, 1]);
+        assert_eq!(turns.runs["p2"], vec![1, 1]);
+        assert_eq!(turns.double_text_rate["p1"], 2.0 / 3.0);
+        assert_eq!(turns.triple_text_rate["p1"], 1.0 / 3.0);
+        assert_eq!(turns.double_text_rate["p2"], 0.0);
+        assert_eq!(turns.turns_per_session, 2.5);
+    }
+
+    #[test]
+    fn test_turn_taking_empty() {
+        let turns = get_turn_taking(&vec![], 100);
+        assert!(turns.runs.is_empty());
+        assert_eq!(turns.turns_per_session, 0.0);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::analysis::tokenizer::{tokenize, TokenizerOptions};
+use crate::parsers::base::BaseMessage;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// A burst without any reply within this window (1 hour) is unanswered.
+pub const DEFAULT_REPLY_WINDOW_MS: i64 = 60 * 60 * 1000;
+
+// cSpell: disable
+/// Words starting a question even without a question mark. Auxiliaries ("do", "is",
+/// "est") start as many statements ("do it", "is fine") and are left out.
+const INTERROGATIVES_EN: &[&str] = &[
+    "who", "whom", "whose", "what", "when", "where", "why", "how", "which",
+];
+const INTERROGATIVES_FR: &[&str] = &[
+    "qui",
+    "quoi",
+    "quand",
+    "où",
+    "pourquoi",
+    "comment",
+    "combien",
+    "quel",
+    "quelle",
+    "quels",
+    "quelles",
+    "lequel",
+    "laquelle",
+    "lesquels",
+    "lesquelles",
+];
+/// Interrogative phrases, as tokens: "est-ce que", "qu'est-ce".
+const INTERROGATIVE_PHRASES_FR: &[&[&str]] = &[&["est", "ce", "que"], &["qu'est", "ce"]];
+// cSpell: enable
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Silence {
+    pub duration_ms: i64,
+    /// When the last message before the silence was sent.
+    pub timestamp_ms: i64,
+    /// Sender and content of the message that preceded the silence.
+    pub sender_name: String,
+    pub content: Option<String>,
+    /// Who broke the silence.
+    pub broken_by: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct UnansweredStats {
+    /// Number of bursts (back to back messages) nobody replied to within the window.
+    pub unanswered_bursts: HashMap<String, i64>,
+    /// Share of the bursts of each participant left unanswered.
+    pub unanswered_rate: HashMap<String, f64>,
+    /// Number of questions asked by each participant.
+    pub questions: HashMap<String, i64>,
+    /// Share of the questions of each participant that got a reply within the window.
+    pub question_answer_rate: HashMap<String, f64>,
+    /// Longest pauses of the conversation, longest first.
+    pub longest_silences: Vec<Silence>,
+}
+
+/// A question mark anywhere, or an interrogative word or phrase first ("how was it",
+/// "est-ce que tu viens").
+pub fn is_question(text: &str) -> bool {
+    if text.contains('?') || text.contains('¿') {
+        return true;
+    }
+    let options = TokenizerOptions {
+        split_elisions: false,
+        ..TokenizerOptions::default()
+    };
+    let tokens = tokenize(text, &options);
+    let first_word = match tokens.first() {
+        Some(first_word) => first_word.as_str(),
+        None => return false,
+    };
+    return INTERROGATIVES_EN.contains(&first_word)
+        || INTERROGATIVES_FR.contains(&first_word)
+        || INTERROGATIVE_PHRASES_FR.iter().any(|phrase| {
+            tokens.len() >= phrase.len() && tokens.iter().zip(phrase.iter()).all(|(a, b)| a == b)
+        });
+}
+
+/// Walk chronologically sorted messages burst by burst: a burst is answered when someone
+/// else writes within `window_ms` of its last message.
+pub fn get_unanswered_stats(
+    messages: &Vec<BaseMessage>,
+    window_ms: i64,
+    num_silences: usize,
+) -> UnansweredStats {
+    let mut bursts: HashMap<String, i64> = HashMap::new();
+    let mut answered_questions: HashMap<String, i64> = HashMap::new();
+    let mut stats = UnansweredStats::default();
+    let mut burst_questions = 0;
+
+    let mut messages_iter = messages.iter().peekable();
+    while let Some(msg) = messages_iter.next() {
+        if msg.content.as_deref().is_some_and(is_question) {
+            burst_questions += 1;
+        }
+
+        let next_msg = messages_iter.peek();
+        if next_msg.is_some_and(|next_msg| next_msg.sender_name == msg.sender_name) {
+            continue;
+        }
+
+        // End of a burst.
+        let answered =
+            next_msg.is_some_and(|next_msg| next_msg.timestamp_ms - msg.timestamp_ms <= window_ms);
+        *bursts.entry(msg.sender_name.clone()).or_insert(0) += 1;
+        *stats
+            .unanswered_bursts
+            .entry(msg.sender_name.clone())
+            .or_insert(0) += if answered { 0 } else { 1 };
+        *stats.questions.entry(msg.sender_name.clone()).or_insert(0) += burst_questions;
+        *answered_questions
+            .entry(msg.sender_name.clone())
+            .or_insert(0) += if answered { burst_questions } else { 0 };
+        burst_questions = 0;
+    }
+
+    for (name, num_bursts) in bursts.iter() {
+        stats.unanswered_rate.insert(
+            name.clone(),
+            stats.unanswered_bursts[name] as f64 / *num_bursts as f64,
+        );
+        if stats.questions[name] > 0 {
+            stats.question_answer_rate.insert(
+                name.clone(),
+                answered_questions[name] as f64 / stats.questions[name] as f64,
+            );
+        }
+    }
+
+    let mut silences: Vec<Silence> = messages
+        .windows(2)
+        .map(|pair| Silence {
+            duration_ms: pair[1].timestamp_ms - pair[0].timestamp_ms,
+            timestamp_ms: pair[0].timestamp_ms,
+            sender_name: pair[0].sender_name.clone(),
+            content: pair[0].content.clone(),
+            broken_by: pair[1].sender_name.clone(),
+        })
+        .collect();
+    silences.sort_by_key(|silence| Reverse(silence.duration_ms));
+    silences.truncate(num_silences);
+    stats.longest_silences = silences;
+
+    return stats;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_unanswered_stats, is_question};
+    use crate::analysis::fixtures::message;
+    use crate::parsers::base::BaseMessage;
+
+    #[test]
+    fn test_is_question() {
+        assert!(is_question("on mange où ?"));
+        assert!(is_question("How was the trip"));
+        assert!(is_question("Pourquoi pas"));
+        assert!(is_question("est-ce que tu viens"));
+        assert!(is_question("qu'est-ce que tu fais"));
+        assert!(!is_question("See you tomorrow."));
+        assert!(!is_question("Do it now"));
+        assert!(!is_question("is fine by me"));
+        assert!(!is_question("est parti ce matin"));
+        assert!(!is_question("😂"));
+    }
+
+    #[test]
+    fn test_unanswered_stats() {
+        let minute = 60 * 1000;
+        let messages: Vec<BaseMessage> = [
+            ("p1", 0, "Are you coming?"),
+            ("p1", minute, "we are at the bar"),
+            ("p2", 2 * minute, "yes!"),
+            ("p2", 3 * minute, "what do you drink?"),
+            ("p1", 5 * 60 * minute, "sorry, I fell asleep"),
+        ]
+        .iter()
+        .map(|(sender, timestamp_ms, content)| message(sender, *timestamp_ms, Some(content)))
+        .collect();
+
+        let stats = get_unanswered_stats(&messages, 60 * minute, 2);
+
+        assert_eq!(stats.questions["p1"], 1);
+        assert_eq!(stats.question_answer_rate["p1"], 1.0);
+        assert_eq!(stats.questions["p2"], 1);
+        assert_eq!(stats.question_answer_rate["p2"], 0.0);
+        // p1: the first burst is answered, the last message never is.
+        assert_eq!(stats.unanswered_bursts["p1"], 1);
+        assert_eq!(stats.unanswered_rate["p1"], 0.5);
+        assert_eq!(stats.unanswered_rate["p2"], 1.0);
+
+        assert_eq!(stats.longest_silences.len(), 2);
+        assert_eq!(stats.longest_silences[0].duration_ms, 297 * minute);
+        assert_eq!(
+            stats.longest_silences[0].content.as_deref(),
+            Some("what do you drink?")
+        );
+        assert_eq!(stats.longest_silences[0].broken_by, "p1");
+    }
+}
@@ -1,32 +1,43 @@
-use clap::Parser;
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
 use crossterm::{
     event::{self, KeyCode, KeyEventKind},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
-use msg::{
-    parsers::base::{
-        get_frequent_words, get_message_counts, get_message_response_times, get_send_hours,
+use msg::analysis::{
+    attribution::{get_attribution_stats, AttributionModel, DEFAULT_TEST_EVERY},
+    filter::MessageFilter,
+    markov::{generate_messages, get_markov_chains, DEFAULT_MAX_WORDS, DEFAULT_ORDER},
+    report::{
+        get_facebook_messages, get_facebook_report, get_whatsapp_messages, get_whatsapp_report,
+        ReportOptions,
     },
-    plots::cli::get_word_plot_cli,
+    stopwords::StopWords,
+    summary::{summarize, DEFAULT_SUMMARY_MESSAGES},
+    timeline::{format_timestamp, Granularity},
+    tracking::{track_terms, Term, TermTracking},
 };
-use msg::{
-    parsers::base::{get_messages_length, get_messages_num},
-    plots::cli::{
-        get_hour_plot_cli, get_message_count_plot_cli, get_message_num_plot_cli,
-        get_reaction_count_plot_cli, get_response_time_plot_cli,
-    },
-};
-use msg::{
-    parsers::{facebook, file},
-    plots::cli::get_message_length_plot_cli,
+use msg::parsers::file;
+use msg::plots::cli::{
+    get_activity_heatmap_cli, get_distinctive_word_plot_cli, get_emoji_plot_cli,
+    get_generated_messages_cli, get_hour_plot_cli, get_media_plot_cli, get_message_count_plot_cli,
+    get_message_length_plot_cli, get_message_num_plot_cli, get_milestones_cli, get_phrase_plot_cli,
+    get_reaction_breakdown_plot_cli, get_reaction_count_plot_cli, get_response_time_plot_cli,
+    get_response_time_stats_cli, get_sentiment_series_cli, get_sentiment_sparkline_cli,
+    get_style_table_cli, get_timeline_chart_cli, get_timeline_points_cli, get_topic_plot_cli,
+    get_turn_taking_stats_cli, get_unanswered_stats_cli, get_word_plot_cli,
 };
 use ratatui::prelude::{Constraint, CrosstermBackend, Direction, Layout, Style, Terminal};
 use ratatui::symbols;
 use ratatui::widgets::{Block, Borders, Tabs};
+use regex::Regex;
 use std::fs::read_to_string;
 use std::io::stdout;
 
+/// Generated messages per participant in the "Talk like" tab.
+const NUM_GENERATED_MESSAGES: usize = 5;
+
 // Cli to parse facebook or whatsapp messages from local files.
 #[derive(Parser, Debug)]
 pub struct CliArgs {
@@ -44,11 +55,129 @@ pub struct CliArgs {
 
     #[arg(short, long)]
     output: String,
+
+    // File of additional stop words, one per line
+    #[arg(long)]
+    stop_words: Option<String>,
+
+    // Minutes without a reply after which a message is unanswered
+    #[arg(long, default_value_t = 60)]
+    reply_window: i64,
+
+    // Emoji whose first use is a milestone, repeat for several (❤️ by default)
+    #[arg(long)]
+    milestone_emoji: Vec<String>,
+
+    // Granularity of the timeline (day, week or month)
+    #[arg(long, default_value = "week")]
+    granularity: String,
+
+    // Only analyse messages sent from this day (YYYY-MM-DD)
+    #[arg(long, global = true)]
+    since: Option<NaiveDate>,
+
+    // Only analyse messages sent until this day included (YYYY-MM-DD)
+    #[arg(long, global = true)]
+    until: Option<NaiveDate>,
+
+    // Only analyse messages of this participant, can be repeated
+    #[arg(long, global = true)]
+    participant: Vec<String>,
+
+    // Only analyse messages whose text matches this regular expression
+    #[arg(long = "match", global = true)]
+    content_match: Option<Regex>,
+
+    // Export instead of opening the terminal interface
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    // Export who answers or mentions whom as GraphML (Gephi) and Graphviz DOT
+    Graph,
+    // Usage of words, phrases or /regular expressions/ over time, with their first and last use
+    Track {
+        #[arg(required = true)]
+        terms: Vec<String>,
+    },
+    // Guess who wrote a text from the writing habits of each participant
+    Attribute {
+        text: String,
+    },
+    // Most representative messages of the period given by --since and --until
+    Summarize {
+        #[arg(long, default_value_t = DEFAULT_SUMMARY_MESSAGES)]
+        num_messages: usize,
+    },
+}
+
+fn print_term_tracking(tracking: &TermTracking) {
+    for usage in tracking.terms.iter() {
+        println!("{}: used {} times", usage.term, usage.total);
+        for (label, occurrence) in [("First", &usage.first), ("Last", &usage.last)] {
+            if let Some(occurrence) = occurrence {
+                println!(
+                    "  {} {} {}: {}",
+                    label,
+                    format_timestamp(occurrence.timestamp_ms),
+                    occurrence.sender_name,
+                    occurrence.content
+                );
+            }
+        }
+
+        let mut names: Vec<&String> = usage.counts.keys().collect();
+        names.sort();
+        for (idx, bucket) in tracking.buckets.iter().enumerate() {
+            let counts: Vec<String> = names
+                .iter()
+                .filter(|name| usage.counts[**name][idx] > 0)
+                .map(|name| format!("{} {}", name, usage.counts[*name][idx]))
+                .collect();
+            if !counts.is_empty() {
+                println!("  {}  {}", bucket, counts.join(", "));
+            }
+        }
+    }
 }
 
 pub fn main() {
     let args = CliArgs::parse();
-    match args.kind.as_str() {
+    let filter = MessageFilter {
+        since: args.since,
+        until: args.until,
+        participants: if args.participant.is_empty() {
+            None
+        } else {
+            Some(args.participant.iter().cloned().collect())
+        },
+        content: args.content_match.clone(),
+        ..MessageFilter::default()
+    };
+    let granularity =
+        Granularity::from_name(&args.granularity).expect("Unknown timeline granularity");
+    let options = ReportOptions {
+        extra_stop_words: match &args.stop_words {
+            Some(stop_words_file) => {
+                read_to_string(stop_words_file).expect("Unable to read stop words file")
+            }
+            None => String::new(),
+        },
+        num_words: 30,
+        num_emojis: 20,
+        reply_window_ms: args.reply_window * 60 * 1000,
+        timelines: vec![(granularity, 4)],
+        milestone_emojis: if args.milestone_emoji.is_empty() {
+            ReportOptions::default().milestone_emojis
+        } else {
+            args.milestone_emoji.clone()
+        },
+        ..ReportOptions::default()
+    };
+
+    let files_data = match args.kind.as_str() {
         "facebook" => {
             let correct_paths = file::facebook_file_parser(&args.files, &args.name);
             let mut files_data = Vec::new();
@@ -56,159 +185,427 @@ pub fn main() {
                 println!("Found {:?} file", file);
                 files_data.push(read_to_string(file).expect("Unable to read file"));
             }
-            let (messages, participants) = facebook::parse_facebook(files_data);
-
-            println!("Found {:?} messages", messages.len());
-
-            let reaction_count = facebook::get_reactions_counts(&messages);
-            let base_messages = messages.into_iter().map(|m| m.into()).collect();
-
-            let msg_plot = get_message_count_plot_cli(&get_message_counts(&base_messages));
-            let reaction_plot = get_reaction_count_plot_cli(&reaction_count);
-            let hours_plot = get_hour_plot_cli(&get_send_hours(&base_messages, &participants));
-            let responses_plot = get_response_time_plot_cli(&get_message_response_times(
-                &base_messages,
-                &participants,
-            ));
-            let message_num_plot = get_message_length_plot_cli(&get_messages_num(&base_messages));
-            let message_length_plot =
-                get_message_num_plot_cli(&get_messages_length(&base_messages));
-            let words = get_frequent_words(&base_messages, 30);
-
-            stdout()
-                .execute(EnterAlternateScreen)
-                .expect("Failed to enter alternate screen");
-
-            enable_raw_mode().expect("Failed to enable raw mode");
-
-            let mut terminal =
-                Terminal::new(CrosstermBackend::new(stdout())).expect("Failed to create terminal");
-            terminal.clear().expect("Failed to clear terminal");
-
-            // TODO : Reformat tabs with a hashmap
-            let tabs_name = vec![
-                "Response",
-                "Message",
-                "Reactions",
-                "Hours",
-                "Words",
-                "Num",
-                "Length",
-            ];
-            let tabs_len = tabs_name.len();
-            let tabs = Tabs::new(tabs_name)
-                .block(Block::default().title("Tabs").borders(Borders::ALL))
-                .style(Style::default())
-                .highlight_style(Style::default())
-                .select(2)
-                .divider(symbols::DOT);
-
-            let mut tab_idx = 0;
-            loop {
-                let _ = terminal.draw(|frame| {
-                    let layout = Layout::default()
+            files_data
+        }
+        "whatsapp" => vec![read_to_string(&args.files).expect("Unable to read file")],
+        _ => {
+            panic!("Unknown kind");
+        }
+    };
+
+    let get_messages = |files_data: Vec<String>| match args.kind.as_str() {
+        "facebook" => get_facebook_messages(files_data, &filter),
+        _ => get_whatsapp_messages(files_data, &filter),
+    };
+    if let Some(Command::Track { terms }) = &args.command {
+        let messages = get_messages(files_data);
+        let terms: Vec<(String, Term)> = terms
+            .iter()
+            .map(|text| (text.clone(), Term::parse(text).expect("Invalid term")))
+            .collect();
+        let tracking = track_terms(&messages, &terms, granularity);
+
+        print_term_tracking(&tracking);
+        file::save_json(&args.output, "tracking.json", &tracking);
+        println!("Saved the term usage to {}/tracking.json", args.output);
+        return;
+    }
+    if let Some(Command::Attribute { text }) = &args.command {
+        let messages = get_messages(files_data);
+        let model = AttributionModel::train(messages.iter());
+        for (name, probability) in model.predict(text) {
+            println!("{:>5.1}% {}", probability * 100.0, name);
+        }
+
+        let stats = get_attribution_stats(&messages, DEFAULT_TEST_EVERY, 5);
+        println!(
+            "Right for {:.1}% of {} held out texts",
+            stats.accuracy * 100.0,
+            stats.num_tested
+        );
+        let mut names: Vec<&String> = stats.participant_accuracy.keys().collect();
+        names.sort();
+        for name in names {
+            let features: Vec<String> = stats.features[name]
+                .iter()
+                .map(|(ngram, _)| format!("{:?}", ngram))
+                .collect();
+            println!(
+                "  {}: {:.1}%, typical {}",
+                name,
+                stats.participant_accuracy[name] * 100.0,
+                features.join(" ")
+            );
+        }
+        return;
+    }
+
+    if let Some(Command::Summarize { num_messages }) = &args.command {
+        let messages = get_messages(files_data);
+        let mut stop_words = StopWords::detect(&messages);
+        stop_words.add_words(&options.extra_stop_words);
+        let summary = summarize(messages.iter(), &stop_words, *num_messages);
+        if summary.is_empty() {
+            println!("No message long enough to summarise");
+        }
+
+        for msg in summary.iter() {
+            println!(
+                "{} {}: {}",
+                format_timestamp(msg.timestamp_ms),
+                msg.sender_name,
+                msg.content
+            );
+        }
+        file::save_json(&args.output, "summary.json", &summary);
+        println!("Saved the summary to {}/summary.json", args.output);
+        return;
+    }
+    let (report, messages) = match args.kind.as_str() {
+        "facebook" => get_facebook_report(files_data, &filter, &options),
+        _ => get_whatsapp_report(files_data, &filter, &options),
+    };
+    println!("Found {:?} messages", report.num_messages);
+
+    file::save_json(&args.output, "report.json", &report);
+    println!("Saved the report to {}/report.json", args.output);
+
+    if let Some(Command::Graph) = args.command {
+        file::save_text(
+            &args.output,
+            "interactions.graphml",
+            &report.interactions.to_graphml(),
+        );
+        file::save_text(
+            &args.output,
+            "interactions.dot",
+            &report.interactions.to_dot(),
+        );
+        println!(
+            "Saved the interaction graph to {}/interactions.graphml and {}/interactions.dot",
+            args.output, args.output
+        );
+        return;
+    }
+
+    let reactions = report.reactions.clone().unwrap_or_default();
+    let msg_plot = get_message_count_plot_cli(&report.message_counts);
+    let reaction_plot = get_reaction_count_plot_cli(&reactions.counts);
+    let reaction_breakdown_plot =
+        get_reaction_breakdown_plot_cli(&reactions.breakdown, &reactions.matrix);
+    let hours_plot = get_hour_plot_cli(&report.send_hours);
+    let responses_plot = get_response_time_plot_cli(&report.response_times);
+    let responses_stats = get_response_time_stats_cli(&report.response_time_summaries);
+    let message_num_plot = get_message_num_plot_cli(&report.turns.runs);
+    let turns_stats = get_turn_taking_stats_cli(&report.turns);
+    let unanswered_stats = get_unanswered_stats_cli(&report.unanswered);
+    let message_length_plot = get_message_length_plot_cli(&report.message_lengths);
+    let words = &report.frequent_words;
+    let distinctive_words = &report.distinctive_words;
+    let distinctive_bigrams = &report.distinctive_bigrams;
+    let phrases = &report.phrases;
+    let emoji_paragraphs = get_emoji_plot_cli(&report.emojis);
+    let sentiment_series = get_sentiment_series_cli(&report.sentiment);
+    let topic_paragraphs = get_topic_plot_cli(&report.topics);
+    let timeline = &report.timelines[0];
+    let timeline_points = get_timeline_points_cli(timeline);
+    let activity_heatmap = &report.activity_heatmap;
+    let media_paragraphs = get_media_plot_cli(&report.media);
+    let style_table = get_style_table_cli(&report.styles);
+    let milestones_paragraph = get_milestones_cli(&report.milestones);
+    let chains = get_markov_chains(&messages, DEFAULT_ORDER);
+    let mut seed = 0;
+    let mut generated_paragraphs = get_generated_messages_cli(&generate_messages(
+        &chains,
+        seed,
+        NUM_GENERATED_MESSAGES,
+        DEFAULT_MAX_WORDS,
+    ));
+    let mut heatmap_tables = vec![get_activity_heatmap_cli(
+        "All".to_string(),
+        &activity_heatmap.combined,
+    )];
+    for name in report.participants.iter() {
+        if let Some(grid) = activity_heatmap.per_participant.get(name) {
+            heatmap_tables.push(get_activity_heatmap_cli(name.clone(), grid));
+        }
+    }
+
+    stdout()
+        .execute(EnterAlternateScreen)
+        .expect("Failed to enter alternate screen");
+
+    enable_raw_mode().expect("Failed to enable raw mode");
+
+    let mut terminal =
+        Terminal::new(CrosstermBackend::new(stdout())).expect("Failed to create terminal");
+    terminal.clear().expect("Failed to clear terminal");
+
+    // TODO : Reformat tabs with a hashmap
+    let tabs_name = vec![
+        "Response",
+        "Message",
+        "Reactions",
+        "Hours",
+        "Words",
+        "Num",
+        "Length",
+        "Emojis",
+        "Phrases",
+        "Sentiment",
+        "Topics",
+        "Timeline",
+        "Heatmap",
+        "Unanswered",
+        "Media",
+        "Style",
+        "Talk like",
+        "Milestones",
+    ];
+    let tabs_len = tabs_name.len();
+    let tabs = Tabs::new(tabs_name)
+        .block(Block::default().title("Tabs").borders(Borders::ALL))
+        .style(Style::default())
+        .highlight_style(Style::default())
+        .select(2)
+        .divider(symbols::DOT);
+
+    let mut tab_idx = 0;
+    loop {
+        let _ = terminal.draw(|frame| {
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(vec![Constraint::Percentage(10), Constraint::Percentage(85)])
+                .split(frame.size());
+
+            frame.render_widget(tabs.clone(), layout[0]);
+
+            let frame_width = frame.size().width;
+            match tab_idx {
+                // TODO : Maybe here compute the bar width from the frame size... ?
+                0 => {
+                    let bar_width = frame_width / (15.0 * 2.5) as u16;
+                    let response_layout = Layout::default()
                         .direction(Direction::Vertical)
-                        .constraints(vec![Constraint::Percentage(10), Constraint::Percentage(85)])
-                        .split(frame.size());
-
-                    frame.render_widget(tabs.clone(), layout[0]);
-
-                    let frame_width = frame.size().width;
-                    match tab_idx {
-                        // TODO : Maybe here compute the bar width from the frame size... ?
-                        0 => {
-                            let bar_width = frame_width / (15.0 * 2.5) as u16;
-                            frame.render_widget(
-                                responses_plot
-                                    .clone()
-                                    .bar_width(bar_width)
-                                    .bar_gap(bar_width / 3)
-                                    .group_gap((bar_width / 4).max(1)),
-                                layout[1],
-                            )
-                        }
-                        1 => frame.render_widget(
-                            msg_plot
-                                .clone()
-                                .bar_width(frame_width / 3)
-                                .bar_gap(frame_width / 6),
-                            layout[1],
-                        ),
-                        2 => frame.render_widget(
-                            reaction_plot
-                                .clone()
-                                .bar_width(frame_width / 3)
-                                .bar_gap(frame_width / 6),
-                            layout[1],
-                        ),
-                        3 => {
-                            let bar_width = frame_width / (24.0 * 2.5) as u16;
-                            frame.render_widget(
-                                hours_plot
-                                    .clone()
-                                    .bar_width(bar_width)
-                                    .bar_gap(bar_width / 3)
-                                    .group_gap((bar_width / 4).max(1)),
-                                layout[1],
-                            )
-                        }
-                        4 => {
-                            let paragraphs = get_word_plot_cli(&words);
-                            let word_layout = Layout::default()
-                                .direction(Direction::Horizontal)
-                                .constraints(vec![
-                                    Constraint::Percentage(
-                                        100 / paragraphs.len() as u16
-                                    );
-                                    paragraphs.len()
-                                ])
-                                .split(layout[1]);
-                            // We know there is only 2 participants.
-                            for (i, paragraph) in paragraphs.iter().enumerate() {
-                                frame.render_widget(paragraph.clone(), word_layout[i]);
-                            }
+                        .constraints(vec![Constraint::Percentage(80), Constraint::Percentage(20)])
+                        .split(layout[1]);
+                    frame.render_widget(
+                        responses_plot
+                            .clone()
+                            .bar_width(bar_width)
+                            .bar_gap(bar_width / 3)
+                            .group_gap((bar_width / 4).max(1)),
+                        response_layout[0],
+                    );
+                    frame.render_widget(responses_stats.clone(), response_layout[1]);
+                }
+                1 => frame.render_widget(
+                    msg_plot
+                        .clone()
+                        .bar_width(frame_width / 3)
+                        .bar_gap(frame_width / 6),
+                    layout[1],
+                ),
+                2 => {
+                    let reaction_layout = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints(vec![Constraint::Percentage(70), Constraint::Percentage(30)])
+                        .split(layout[1]);
+                    frame.render_widget(
+                        reaction_plot
+                            .clone()
+                            .bar_width(frame_width / 3)
+                            .bar_gap(frame_width / 6),
+                        reaction_layout[0],
+                    );
+                    frame.render_widget(reaction_breakdown_plot.clone(), reaction_layout[1]);
+                }
+                3 => {
+                    let bar_width = frame_width / (24.0 * 2.5) as u16;
+                    frame.render_widget(
+                        hours_plot
+                            .clone()
+                            .bar_width(bar_width)
+                            .bar_gap(bar_width / 3)
+                            .group_gap((bar_width / 4).max(1)),
+                        layout[1],
+                    )
+                }
+                4 => {
+                    let words_layout = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
+                        .split(layout[1]);
+
+                    let rows = [
+                        get_word_plot_cli(words),
+                        get_distinctive_word_plot_cli(distinctive_words, distinctive_bigrams),
+                    ];
+                    for (row, paragraphs) in rows.iter().enumerate() {
+                        let word_layout = Layout::default()
+                            .direction(Direction::Horizontal)
+                            .constraints(vec![
+                                Constraint::Percentage(
+                                    100 / paragraphs.len().max(1) as u16
+                                );
+                                paragraphs.len()
+                            ])
+                            .split(words_layout[row]);
+                        for (i, paragraph) in paragraphs.iter().enumerate() {
+                            frame.render_widget(paragraph.clone(), word_layout[i]);
                         }
-                        5 => frame.render_widget(
-                            message_num_plot
-                                .clone()
-                                .bar_width(3)
-                                .bar_gap(1)
-                                .group_gap(2),
-                            layout[1],
-                        ),
-                        6 => frame.render_widget(
-                            message_length_plot
-                                .clone()
-                                .bar_width(3)
-                                .bar_gap(1)
-                                .group_gap(2),
-                            layout[1],
-                        ),
-                        _ => {}
                     }
-                });
-
-                if event::poll(std::time::Duration::from_millis(16)).expect("Failed to poll event")
-                {
-                    if let event::Event::Key(key) = event::read().expect("Failed to read event") {
-                        if key.kind == KeyEventKind::Press {
-                            match key.code {
-                                KeyCode::Char('q') => break,
-                                KeyCode::Tab => tab_idx = (tab_idx + 1) % tabs_len,
-                                _ => {}
-                            }
-                        }
+                }
+                5 => {
+                    let num_layout = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints(vec![Constraint::Percentage(75), Constraint::Percentage(25)])
+                        .split(layout[1]);
+                    frame.render_widget(
+                        message_num_plot
+                            .clone()
+                            .bar_width(3)
+                            .bar_gap(1)
+                            .group_gap(2),
+                        num_layout[0],
+                    );
+                    frame.render_widget(turns_stats.clone(), num_layout[1]);
+                }
+                6 => frame.render_widget(
+                    message_length_plot
+                        .clone()
+                        .bar_width(3)
+                        .bar_gap(1)
+                        .group_gap(2),
+                    layout[1],
+                ),
+                7 => {
+                    let emoji_layout = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints(vec![
+                            Constraint::Percentage(
+                                100 / emoji_paragraphs.len().max(1) as u16
+                            );
+                            emoji_paragraphs.len()
+                        ])
+                        .split(layout[1]);
+                    for (i, paragraph) in emoji_paragraphs.iter().enumerate() {
+                        frame.render_widget(paragraph.clone(), emoji_layout[i]);
                     }
                 }
+                8 => {
+                    let paragraphs = get_phrase_plot_cli(phrases);
+                    let phrase_layout = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints(vec![
+                            Constraint::Percentage(
+                                100 / paragraphs.len().max(1) as u16
+                            );
+                            paragraphs.len()
+                        ])
+                        .split(layout[1]);
+                    for (i, paragraph) in paragraphs.iter().enumerate() {
+                        frame.render_widget(paragraph.clone(), phrase_layout[i]);
+                    }
+                }
+                9 => {
+                    let sentiment_layout = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints(vec![
+                            Constraint::Percentage(
+                                100 / sentiment_series.len() as u16
+                            );
+                            sentiment_series.len()
+                        ])
+                        .split(layout[1]);
+                    for (i, (title, data)) in sentiment_series.iter().enumerate() {
+                        frame.render_widget(
+                            get_sentiment_sparkline_cli(title, data),
+                            sentiment_layout[i],
+                        );
+                    }
+                }
+                10 => {
+                    let topic_layout = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
+                        .split(layout[1]);
+                    for (i, paragraph) in topic_paragraphs.iter().enumerate() {
+                        frame.render_widget(paragraph.clone(), topic_layout[i]);
+                    }
+                }
+                11 => frame.render_widget(
+                    get_timeline_chart_cli(timeline, &timeline_points),
+                    layout[1],
+                ),
+                12 => {
+                    let heatmap_layout = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints(vec![Constraint::Length(10); heatmap_tables.len()])
+                        .split(layout[1]);
+                    for (i, table) in heatmap_tables.iter().enumerate() {
+                        frame.render_widget(table.clone(), heatmap_layout[i]);
+                    }
+                }
+                13 => frame.render_widget(unanswered_stats.clone(), layout[1]),
+                14 => {
+                    let media_layout = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints(vec![
+                            Constraint::Percentage(
+                                100 / media_paragraphs.len() as u16
+                            );
+                            media_paragraphs.len()
+                        ])
+                        .split(layout[1]);
+                    for (i, paragraph) in media_paragraphs.iter().enumerate() {
+                        frame.render_widget(paragraph.clone(), media_layout[i]);
+                    }
+                }
+                15 => frame.render_widget(style_table.clone(), layout[1]),
+                16 => {
+                    let generated_layout = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints(vec![
+                            Constraint::Percentage(
+                                100 / generated_paragraphs.len().max(1) as u16
+                            );
+                            generated_paragraphs.len()
+                        ])
+                        .split(layout[1]);
+                    for (i, paragraph) in generated_paragraphs.iter().enumerate() {
+                        frame.render_widget(paragraph.clone(), generated_layout[i]);
+                    }
+                }
+                17 => frame.render_widget(milestones_paragraph.clone(), layout[1]),
+                _ => {}
             }
+        });
 
-            stdout()
-                .execute(LeaveAlternateScreen)
-                .expect("Failed to leave alternate screen");
-            disable_raw_mode().expect("Failed to disable raw mode");
-        }
-        _ => {
-            panic!("Unknown kind");
+        if event::poll(std::time::Duration::from_millis(16)).expect("Failed to poll event") {
+            if let event::Event::Key(key) = event::read().expect("Failed to read event") {
+                if key.kind == KeyEventKind::Press {
+                    match key.code {
+                        KeyCode::Char('q') => break,
+                        KeyCode::Tab => tab_idx = (tab_idx + 1) % tabs_len,
+                        KeyCode::Char('g') => {
+                            seed += NUM_GENERATED_MESSAGES as u64;
+                            generated_paragraphs = get_generated_messages_cli(&generate_messages(
+                                &chains,
+                                seed,
+                                NUM_GENERATED_MESSAGES,
+                                DEFAULT_MAX_WORDS,
+                            ));
+                        }
+                        _ => {}
+                    }
+                }
+            }
         }
     }
+
+    stdout()
+        .execute(LeaveAlternateScreen)
+        .expect("Failed to leave alternate screen");
+    disable_raw_mode().expect("Failed to disable raw mode");
 }
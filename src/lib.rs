@@ -1,3 +1,4 @@
+pub mod analysis;
 pub mod parsers;
 
 pub mod web;
@@ -1,7 +1,10 @@
+use crate::analysis::stopwords::StopWords;
+use crate::analysis::tokenizer::{tokenize, TokenizerOptions};
 use chrono::{DateTime, Timelike};
 use serde::{Deserialize, Serialize};
 
 use std::collections::{HashMap, HashSet};
+use unicode_segmentation::UnicodeSegmentation;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct BaseMessage {
@@ -10,344 +13,6 @@ pub struct BaseMessage {
     pub content: Option<String>,
 }
 
-// cSpell: disable
-const STOP_WORDS: &[&str] = &[
-    "a",
-    "à",
-    "ã",
-    "ã§a",
-    "â¤",
-    "ãa",
-    "ãªtre",
-    "about",
-    "above",
-    "after",
-    "again",
-    "against",
-    "ai",
-    "aie",
-    "aient",
-    "aies",
-    "ait",
-    "all",
-    "aller",
-    "alors",
-    "am",
-    "an",
-    "and",
-    "any",
-    "are",
-    "as",
-    "at",
-    "au",
-    "aura",
-    "aurai",
-    "auraient",
-    "aurais",
-    "aurait",
-    "auras",
-    "aurez",
-    "auriez",
-    "aurions",
-    "aurons",
-    "auront",
-    "aussi",
-    "aux",
-    "avaient",
-    "avais",
-    "avait",
-    "avec",
-    "avez",
-    "aviez",
-    "avions",
-    "avoir",
-    "avons",
-    "ayant",
-    "ayez",
-    "ayons",
-    "be",
-    "because",
-    "been",
-    "before",
-    "being",
-    "below",
-    "between",
-    "bien",
-    "bon",
-    "both",
-    "but",
-    "by",
-    "c'est",
-    "c",
-    "ca",
-    "can",
-    "ce",
-    "ceci",
-    "cela",
-    "ces",
-    "cet",
-    "cette",
-    "d",
-    "ð",
-    "ð",
-    "ð\u{9f}\u{98}",
-    "ð\u{9f}\u{98}\u{82}",
-    "dans",
-    "de",
-    "des",
-    "did",
-    "do",
-    "does",
-    "doing",
-    "don",
-    "down",
-    "du",
-    "during",
-    "each",
-    "elle",
-    "en",
-    "es",
-    "est",
-    "et",
-    "étaient",
-    "étais",
-    "était",
-    "ete",
-    "été",
-    "êtes",
-    "étiez",
-    "étions",
-    "etre",
-    "être",
-    "eu",
-    "eue",
-    "eues",
-    "eûmes",
-    "eurent",
-    "eus",
-    "eusse",
-    "eussent",
-    "eusses",
-    "eussiez",
-    "eussions",
-    "eut",
-    "eût",
-    "eûtes",
-    "eux",
-    "faire",
-    "fais",
-    "fait",
-    "few",
-    "for",
-    "from",
-    "fûmes",
-    "furent",
-    "further",
-    "fus",
-    "fusse",
-    "fussent",
-    "fusses",
-    "fussiez",
-    "fussions",
-    "fut",
-    "fût",
-    "fûtes",
-    "ha",
-    "had",
-    "haha",
-    "has",
-    "have",
-    "having",
-    "he",
-    "her",
-    "here",
-    "hers",
-    "herself",
-    "him",
-    "himself",
-    "his",
-    "how",
-    "i",
-    "ici",
-    "if",
-    "il",
-    "ils",
-    "in",
-    "into",
-    "is",
-    "it",
-    "its",
-    "itself",
-    "j'ai",
-    "j",
-    "je",
-    "just",
-    "l",
-    "la",
-    "le",
-    "les",
-    "leur",
-    "leurs",
-    "lui",
-    "m",
-    "ma",
-    "mãªme",
-    "mÃªme",
-    "mais",
-    "me",
-    "meme",
-    "même",
-    "mes",
-    "message",
-    "moi",
-    "mon",
-    "more",
-    "most",
-    "my",
-    "myself",
-    "n",
-    "ne",
-    "no",
-    "nor",
-    "nos",
-    "not",
-    "notre",
-    "nous",
-    "now",
-    "of",
-    "off",
-    "oh",
-    "Oh",
-    "on",
-    "once",
-    "only",
-    "ont",
-    "or",
-    "other",
-    "ou",
-    "où",
-    "oui",
-    "Oui",
-    "our",
-    "ours",
-    "ourselves",
-    "out",
-    "over",
-    "own",
-    "par",
-    "pas",
-    "peu",
-    "peut",
-    "peux",
-    "plus",
-    "pour",
-    "qu",
-    "quand",
-    "que",
-    "quel",
-    "quelle",
-    "quelles",
-    "quels",
-    "qui",
-    "rã©agi",
-    "rÃ©agi",
-    "s",
-    "sa",
-    "sais",
-    "same",
-    "sans",
-    "se",
-    "sera",
-    "serai",
-    "seraient",
-    "serais",
-    "serait",
-    "seras",
-    "serez",
-    "seriez",
-    "serions",
-    "serons",
-    "seront",
-    "ses",
-    "she",
-    "should",
-    "si",
-    "so",
-    "soi",
-    "soient",
-    "sois",
-    "soit",
-    "some",
-    "sommes",
-    "son",
-    "sont",
-    "soyez",
-    "soyons",
-    "such",
-    "suis",
-    "sur",
-    "t",
-    "ta",
-    "te",
-    "tes",
-    "than",
-    "that",
-    "the",
-    "their",
-    "theirs",
-    "them",
-    "themselves",
-    "then",
-    "there",
-    "these",
-    "they",
-    "this",
-    "those",
-    "through",
-    "to",
-    "toi",
-    "ton",
-    "too",
-    "tout",
-    "trop",
-    "tu",
-    "un",
-    "under",
-    "une",
-    "until",
-    "up",
-    "va",
-    "vais",
-    "very",
-    "veux",
-    "vos",
-    "votre",
-    "vous",
-    "was",
-    "we",
-    "were",
-    "what",
-    "when",
-    "where",
-    "which",
-    "while",
-    "who",
-    "whom",
-    "why",
-    "will",
-    "with",
-    "y",
-    "you",
-    "your",
-    "ð",
-    "ð",
-    "ð",
-    "yours",
-    "yourself",
-    "yourselves",
-];
-// cSpell: enable
-
 pub fn get_message_counts(messages: &Vec<BaseMessage>) -> HashMap<String, i32> {
     let mut msg_count: HashMap<String, i32> = HashMap::new();
     for msg in messages {
@@ -365,31 +30,24 @@ pub fn get_message_counts(messages: &Vec<BaseMessage>) -> HashMap<String, i32> {
 pub fn get_messages_num(messages: &Vec<BaseMessage>) -> HashMap<String, Vec<i64>> {
     let mut msg_count: HashMap<String, Vec<i64>> = HashMap::new();
     let mut current_sender_num_msg = 0;
-    let mut current_sender = "".to_string();
 
-    for msg in messages {
-        let msg_sender = msg.sender_name.clone();
-        if msg.sender_name != current_sender {
-            if current_sender == "" {
-                current_sender = msg_sender;
-                current_sender_num_msg += 1;
-            } else {
-                msg_count
-                    .entry(current_sender)
-                    .and_modify(|v| v.push(current_sender_num_msg))
-                    .or_insert_with(|| vec![current_sender_num_msg]);
-                current_sender_num_msg = 0;
-                current_sender = msg_sender;
-            }
-        } else {
-            current_sender_num_msg += 1;
-        }
-    }
+    let mut messages_iter = messages.iter().peekable();
 
-    if current_sender_num_msg > 0 {
-        msg_count
-            .entry(current_sender)
-            .and_modify(|v| v.push(current_sender_num_msg));
+    while let Some(msg) = messages_iter.next() {
+        current_sender_num_msg += 1;
+
+        let run_ends = match messages_iter.peek() {
+            Some(next_msg) => next_msg.sender_name != msg.sender_name,
+            None => true,
+        };
+
+        if run_ends {
+            msg_count
+                .entry(msg.sender_name.clone())
+                .or_default()
+                .push(current_sender_num_msg);
+            current_sender_num_msg = 0;
+        }
     }
 
     return msg_count;
@@ -398,20 +56,22 @@ pub fn get_messages_num(messages: &Vec<BaseMessage>) -> HashMap<String, Vec<i64>
 pub fn get_frequent_words(
     messages: &Vec<BaseMessage>,
     num_words: usize,
+    stop_words: &StopWords,
 ) -> HashMap<String, Vec<String>> {
     let mut word_counts: HashMap<String, HashMap<String, i64>> = HashMap::new();
+    let tokenizer_options = TokenizerOptions::default();
 
     for msg in messages {
         if let Some(content) = &msg.content {
-            for word in content.split_whitespace() {
-                if STOP_WORDS.contains(&word.to_lowercase().as_str()) {
+            for word in tokenize(content, &tokenizer_options) {
+                if stop_words.contains(&word) {
                     continue;
                 }
 
                 word_counts
                     .entry(msg.sender_name.clone())
                     .or_insert(HashMap::new())
-                    .entry(word.to_string())
+                    .entry(word)
                     .and_modify(|e| *e += 1)
                     .or_insert(1);
             }
@@ -421,7 +81,7 @@ pub fn get_frequent_words(
     let mut msg_count: HashMap<String, Vec<String>> = HashMap::new();
     for (k, v) in word_counts.iter() {
         let mut top_strings: Vec<(&String, &i64)> = v.iter().collect();
-        top_strings.sort_by(|a, b| b.1.cmp(a.1));
+        top_strings.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
         msg_count.insert(
             k.to_string(),
             top_strings
@@ -435,32 +95,17 @@ pub fn get_frequent_words(
     return msg_count;
 }
 
-// pub fn get_emojis(messages: &Vec<BaseMessage>) -> HashMap<String, Vec<String>> {
-//     let mut msg_count: HashMap<String, i32> = HashMap::new();
-//     for msg in messages {
-//         let sender = msg.sender_name.clone();
-//         match msg_count.get(&sender) {
-//             Some(count) => {
-//                 msg_count.insert(sender.to_string(), count + 1);
-//             }
-//             None => {
-//                 msg_count.insert(sender.to_string(), 1);
-//             }
-//         }
-//     }
-
-//     return msg_count;
-// }
-
+/// Length of every text in graphemes, so that accents and emojis count as one character.
 pub fn get_messages_length(messages: &Vec<BaseMessage>) -> HashMap<String, Vec<i64>> {
     let mut msg_count: HashMap<String, Vec<i64>> = HashMap::new();
     for msg in messages {
         let sender = msg.sender_name.clone();
         if let Some(ct) = &msg.content {
+            let length = ct.graphemes(true).count() as i64;
             msg_count
                 .entry(sender)
-                .and_modify(|e| e.push(ct.len() as i64))
-                .or_insert(vec![ct.len() as i64]);
+                .and_modify(|e| e.push(length))
+                .or_insert(vec![length]);
         }
     }
 
@@ -489,9 +134,12 @@ pub fn get_send_hours(
     return message_hours;
 }
 
+/// Time (in seconds) each participant takes to answer someone else. A message sent after a
+/// pause longer than `gap_ms` starts a new conversation session: it is not a response.
 pub fn get_message_response_times(
     messages: &Vec<BaseMessage>,
     participants: &HashSet<String>,
+    gap_ms: i64,
 ) -> HashMap<String, Vec<i64>> {
     let mut response_times: HashMap<String, Vec<i64>> = HashMap::new();
 
@@ -503,16 +151,10 @@ pub fn get_message_response_times(
 
     while let Some(msg) = messages_iter.next() {
         if let Some(next_msg) = messages_iter.peek() {
-            if next_msg.sender_name != msg.sender_name {
+            let response_delta_in_ms = next_msg.timestamp_ms - msg.timestamp_ms;
+            if next_msg.sender_name != msg.sender_name && response_delta_in_ms <= gap_ms {
                 if let Some(sender_response_times) = response_times.get_mut(&next_msg.sender_name) {
-                    let mut response_delta_in_seconds =
-                        (next_msg.timestamp_ms - msg.timestamp_ms) / 1000;
-
-                    if response_delta_in_seconds > 86400 {
-                        response_delta_in_seconds = 86400
-                    }
-
-                    sender_response_times.push(response_delta_in_seconds);
+                    sender_response_times.push(response_delta_in_ms / 1000);
                 }
             }
         }
@@ -520,3 +162,78 @@ pub fn get_message_response_times(
 
     return response_times;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_frequent_words, get_message_response_times, get_messages_length, get_messages_num,
+        BaseMessage,
+    };
+    use crate::analysis::fixtures::message;
+    use crate::analysis::stopwords::{Language, StopWords};
+    use std::collections::HashSet;
+
+    fn messages_from(senders: &[&str]) -> Vec<BaseMessage> {
+        senders
+            .iter()
+            .enumerate()
+            .map(|(idx, sender)| message(sender, idx as i64, None))
+            .collect()
+    }
+
+    #[test]
+    fn test_messages_num() {
+        let messages = messages_from(&["p1", "p1", "p1", "p2", "p2", "p1", "p1", "p2", "p2", "p2"]);
+        let num = get_messages_num(&messages);
+        assert_eq!(num["p1"], vec![3, 2]);
+        assert_eq!(num["p2"], vec![2, 3]);
+    }
+
+    #[test]
+    fn test_messages_num_single_run() {
+        let num = get_messages_num(&messages_from(&["p1", "p1"]));
+        assert_eq!(num["p1"], vec![2]);
+        assert_eq!(num.len(), 1);
+    }
+
+    #[test]
+    fn test_frequent_words() {
+        let messages: Vec<BaseMessage> = ["Salut! Tu viens?", "salut, j'arrive", "SALUT 😂"]
+            .iter()
+            .map(|content| message("p1", 0, Some(content)))
+            .collect();
+
+        let words = get_frequent_words(&messages, 2, &StopWords::new(&[Language::French]));
+        assert_eq!(words["p1"], vec!["salut", "arrive"]);
+    }
+
+    #[test]
+    fn test_messages_length() {
+        let messages: Vec<BaseMessage> = ["déjà 😂", "ok"]
+            .iter()
+            .map(|content| message("p1", 0, Some(content)))
+            .collect();
+
+        assert_eq!(get_messages_length(&messages)["p1"], vec![6, 2]);
+    }
+
+    #[test]
+    fn test_response_times_within_sessions() {
+        let minute = 60 * 1000;
+        let messages: Vec<BaseMessage> = [
+            ("p1", 0),
+            ("p2", minute),
+            ("p2", 2 * minute),
+            ("p1", 3 * minute),
+            ("p2", 10 * 60 * minute),
+        ]
+        .iter()
+        .map(|(sender, timestamp_ms)| message(sender, *timestamp_ms, None))
+        .collect();
+        let participants = HashSet::from(["p1".to_string(), "p2".to_string()]);
+
+        let response_times = get_message_response_times(&messages, &participants, 30 * minute);
+        assert_eq!(response_times["p2"], vec![60]);
+        assert_eq!(response_times["p1"], vec![60]);
+    }
+}
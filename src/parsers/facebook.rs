@@ -1,3 +1,8 @@
+use crate::analysis::filter::MessageFilter;
+use crate::analysis::media::{get_links, MediaKind, SharedMedia};
+use crate::analysis::reacted::ReactedMessage;
+use crate::analysis::report::{MessengerActivity, ReactionStats};
+use crate::analysis::stats::median;
 use crate::parsers::base::BaseMessage;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -8,8 +13,14 @@ struct FacebookGif {
 
 #[derive(Serialize, Deserialize, Clone)]
 struct FacebookShare {
-    share_text: String,
-    link: String,
+    share_text: Option<String>,
+    link: Option<String>,
+}
+
+/// Videos, audio files, stickers and other files.
+#[derive(Serialize, Deserialize, Clone)]
+struct FacebookAttachment {
+    uri: String,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -31,8 +42,20 @@ pub struct FacebookMessage {
     content: Option<String>,
     photos: Option<Vec<FacebookPhoto>>,
     reactions: Option<Vec<FacebookReaction>>,
-    shares: Option<FacebookShare>,
-    gif: Option<Vec<FacebookGif>>,
+    share: Option<FacebookShare>,
+    gifs: Option<Vec<FacebookGif>>,
+    videos: Option<Vec<FacebookAttachment>>,
+    audio_files: Option<Vec<FacebookAttachment>>,
+    sticker: Option<FacebookAttachment>,
+    files: Option<Vec<FacebookAttachment>>,
+    /// Length of calls in seconds, 0 when nobody answered.
+    call_duration: Option<i64>,
+    is_unsent: Option<bool>,
+    /// Hidden from the person who exported the conversation, only a placeholder is left.
+    is_geoblocked_for_viewer: Option<bool>,
+    /// "Generic", "Share", "Call"... only in older exports.
+    #[serde(rename = "type")]
+    message_type: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -46,10 +69,79 @@ pub struct FacebookMessenger {
     pub messages: Vec<FacebookMessage>,
 }
 
+/// Facebook exports UTF-8 text escaped byte per byte (mojibake like "Ã©" for "é").
+/// Re-interpret such strings as bytes and decode them, keep them untouched otherwise.
+pub fn fix_facebook_encoding(text: &str) -> String {
+    if text.chars().all(|c| (c as u32) <= 0xFF) {
+        let bytes: Vec<u8> = text.chars().map(|c| c as u8).collect();
+        if let Ok(decoded) = String::from_utf8(bytes) {
+            return decoded;
+        }
+    }
+    return text.to_string();
+}
+
+impl FacebookMessage {
+    pub fn matches(&self, filter: &MessageFilter) -> bool {
+        return filter.matches(&self.sender_name, self.timestamp_ms, self.text());
+    }
+
+    /// Calls, unsent or hidden messages and events ("Subscribe"...) only have a content
+    /// written by Messenger: "You called…", "… unsent a message". Shares keep the text
+    /// sent along the link.
+    fn is_placeholder(&self) -> bool {
+        return self.is_call()
+            || self.is_unsent == Some(true)
+            || self.is_geoblocked_for_viewer == Some(true)
+            || !matches!(
+                self.message_type.as_deref(),
+                None | Some("Generic") | Some("Share")
+            );
+    }
+
+    /// Content written by the sender, `None` for placeholders.
+    fn text(&self) -> Option<&str> {
+        if self.is_placeholder() {
+            return None;
+        }
+        return self.content.as_deref();
+    }
+
+    fn is_call(&self) -> bool {
+        return self.call_duration.is_some() || self.message_type.as_deref() == Some("Call");
+    }
+
+    fn has_attachments(&self) -> bool {
+        return self.photos.is_some()
+            || self.videos.is_some()
+            || self.gifs.is_some()
+            || self.audio_files.is_some()
+            || self.sticker.is_some()
+            || self.files.is_some();
+    }
+
+    fn fix_encoding(&mut self) {
+        self.sender_name = fix_facebook_encoding(&self.sender_name);
+        self.content = self.content.as_deref().map(fix_facebook_encoding);
+
+        if let Some(reactions) = &mut self.reactions {
+            for reaction in reactions.iter_mut() {
+                reaction.reaction = fix_facebook_encoding(&reaction.reaction);
+                reaction.actor = fix_facebook_encoding(&reaction.actor);
+            }
+        }
+    }
+}
+
+/// Placeholders lose their content, so that the text analyses only see what was written.
 impl Into<BaseMessage> for FacebookMessage {
     fn into(self) -> BaseMessage {
         return BaseMessage {
-            content: self.content,
+            content: if self.is_placeholder() {
+                None
+            } else {
+                self.content
+            },
             sender_name: self.sender_name,
             timestamp_ms: self.timestamp_ms,
         };
@@ -61,12 +153,16 @@ pub fn parse_facebook(files: Vec<String>) -> (Vec<FacebookMessage>, HashSet<std:
     let mut participants: HashSet<String> = HashSet::new();
 
     for file_content in files.iter() {
-        let fb: FacebookMessenger =
+        let mut fb: FacebookMessenger =
             serde_json::from_str(file_content).expect("Unable to create facebook object");
+
+        for msg in fb.messages.iter_mut() {
+            msg.fix_encoding();
+        }
         messages.extend(fb.messages);
 
         for p in fb.participants {
-            participants.insert(p.name);
+            participants.insert(fix_facebook_encoding(&p.name));
         }
     }
 
@@ -75,7 +171,6 @@ pub fn parse_facebook(files: Vec<String>) -> (Vec<FacebookMessage>, HashSet<std:
     return (messages, participants);
 }
 pub fn get_reactions_counts(messages: &Vec<FacebookMessage>) -> HashMap<String, i32> {
-    // HashMap<String, HashMap<String, i32>> (per person per reaction)
     let mut reaction_count = HashMap::new();
 
     for msg in messages {
@@ -100,9 +195,183 @@ pub fn get_reactions_counts(messages: &Vec<FacebookMessage>) -> HashMap<String,
     return reaction_count;
 }
 
+/// Number of each reaction type given by each actor (reactions to oneself are ignored).
+/// {actor: {reaction: count}}
+pub fn get_reactions_breakdown(
+    messages: &Vec<FacebookMessage>,
+) -> HashMap<String, HashMap<String, i32>> {
+    let mut breakdown: HashMap<String, HashMap<String, i32>> = HashMap::new();
+
+    for msg in messages {
+        if let Some(reactions) = &msg.reactions {
+            for reaction in reactions {
+                if reaction.actor != msg.sender_name {
+                    *breakdown
+                        .entry(reaction.actor.clone())
+                        .or_default()
+                        .entry(reaction.reaction.clone())
+                        .or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    return breakdown;
+}
+
+/// Who reacts to whom: number of reactions each actor gave to the messages of each sender.
+/// {actor: {sender: count}}
+pub fn get_reactions_matrix(
+    messages: &Vec<FacebookMessage>,
+) -> HashMap<String, HashMap<String, i32>> {
+    let mut matrix: HashMap<String, HashMap<String, i32>> = HashMap::new();
+
+    for msg in messages {
+        if let Some(reactions) = &msg.reactions {
+            for reaction in reactions {
+                if reaction.actor != msg.sender_name {
+                    *matrix
+                        .entry(reaction.actor.clone())
+                        .or_default()
+                        .entry(msg.sender_name.clone())
+                        .or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    return matrix;
+}
+
+/// Photos, videos, GIFs, stickers, audio files and links of every message. Links come from
+/// the shares, or from the text when nothing was shared.
+pub fn get_facebook_media(messages: &Vec<FacebookMessage>) -> Vec<SharedMedia> {
+    let mut media: Vec<SharedMedia> = Vec::new();
+
+    for msg in messages {
+        let shared_media = |kind: MediaKind, url: Option<String>| SharedMedia {
+            sender_name: msg.sender_name.clone(),
+            timestamp_ms: msg.timestamp_ms,
+            kind,
+            url,
+        };
+
+        let attachments = [
+            (MediaKind::Photo, msg.photos.as_ref().map_or(0, |x| x.len())),
+            (MediaKind::Video, msg.videos.as_ref().map_or(0, |x| x.len())),
+            (MediaKind::Gif, msg.gifs.as_ref().map_or(0, |x| x.len())),
+            (
+                MediaKind::Audio,
+                msg.audio_files.as_ref().map_or(0, |x| x.len()),
+            ),
+            (MediaKind::Sticker, msg.sticker.iter().len()),
+            (MediaKind::Other, msg.files.as_ref().map_or(0, |x| x.len())),
+        ];
+        for (kind, count) in attachments {
+            for _ in 0..count {
+                media.push(shared_media(kind, None));
+            }
+        }
+
+        match msg.share.as_ref().and_then(|share| share.link.clone()) {
+            Some(link) => media.push(shared_media(MediaKind::Link, Some(link))),
+            None => {
+                for link in get_links(msg.content.as_deref().unwrap_or_default()) {
+                    media.push(shared_media(MediaKind::Link, Some(link)));
+                }
+            }
+        }
+    }
+
+    return media;
+}
+
+/// Every message with the other participants who reacted to it.
+pub fn get_reacted_messages(messages: &Vec<FacebookMessage>) -> Vec<ReactedMessage> {
+    return messages
+        .iter()
+        .map(|msg| ReactedMessage {
+            sender_name: msg.sender_name.clone(),
+            timestamp_ms: msg.timestamp_ms,
+            content: msg.text().map(|text| text.to_string()),
+            has_media: msg.has_attachments(),
+            actors: msg
+                .reactions
+                .iter()
+                .flatten()
+                .filter(|reaction| reaction.actor != msg.sender_name)
+                .map(|reaction| reaction.actor.clone())
+                .collect(),
+        })
+        .collect();
+}
+
+pub fn get_reaction_stats(messages: &Vec<FacebookMessage>) -> ReactionStats {
+    return ReactionStats {
+        counts: get_reactions_counts(messages),
+        breakdown: get_reactions_breakdown(messages),
+        matrix: get_reactions_matrix(messages),
+    };
+}
+
+/// Calls, unsent messages and stickers of each participant. Calls are attributed to the
+/// participant who started them.
+pub fn get_messenger_activity(messages: &Vec<FacebookMessage>) -> MessengerActivity {
+    let mut activity = MessengerActivity::default();
+    let mut message_counts: HashMap<String, i32> = HashMap::new();
+    let mut unsent_counts: HashMap<String, i32> = HashMap::new();
+    let mut durations: HashMap<String, Vec<f64>> = HashMap::new();
+
+    for msg in messages {
+        let sender = &msg.sender_name;
+        *message_counts.entry(sender.clone()).or_insert(0) += 1;
+        if msg.is_unsent == Some(true) {
+            *unsent_counts.entry(sender.clone()).or_insert(0) += 1;
+        }
+        if msg.sticker.is_some() {
+            *activity.stickers.entry(sender.clone()).or_insert(0) += 1;
+        }
+
+        if msg.is_call() {
+            *activity.calls.entry(sender.clone()).or_insert(0) += 1;
+            let duration = msg.call_duration.unwrap_or(0);
+            if duration == 0 {
+                *activity.missed_calls.entry(sender.clone()).or_insert(0) += 1;
+            } else {
+                *activity
+                    .total_call_duration_s
+                    .entry(sender.clone())
+                    .or_insert(0) += duration;
+                durations
+                    .entry(sender.clone())
+                    .or_default()
+                    .push(duration as f64);
+            }
+        }
+    }
+
+    for (name, count) in message_counts.iter() {
+        let unsent = unsent_counts.get(name).copied().unwrap_or(0);
+        activity
+            .unsent_rate
+            .insert(name.clone(), unsent as f64 / *count as f64);
+    }
+    for (name, durations) in durations.iter() {
+        if let Some(median) = median(durations) {
+            activity.median_call_duration_s.insert(name.clone(), median);
+        }
+    }
+
+    return activity;
+}
+
 #[cfg(test)]
 mod tests {
-    use super::parse_facebook;
+    use super::{
+        fix_facebook_encoding, get_messenger_activity, get_reactions_breakdown,
+        get_reactions_matrix, parse_facebook,
+    };
+    use crate::parsers::base::BaseMessage;
     use std::fs::read_to_string;
 
     #[test]
@@ -124,4 +393,73 @@ mod tests {
         assert_eq!(messages.len(), 16);
         assert_eq!(participants.len(), 2);
     }
+
+    #[test]
+    fn test_fix_facebook_encoding() {
+        assert_eq!(fix_facebook_encoding("\u{e2}\u{9d}\u{a4}"), "❤");
+        assert_eq!(fix_facebook_encoding("d\u{c3}\u{a9}j\u{c3}\u{a0}"), "déjà");
+        assert_eq!(fix_facebook_encoding("déjà"), "déjà");
+        assert_eq!(fix_facebook_encoding("plain"), "plain");
+    }
+
+    #[test]
+    fn test_reactions_breakdown() {
+        let (messages, _) = parse_facebook(vec![read_to_string(String::from(
+            "./tests/assets/message_1.json",
+        ))
+        .unwrap()]);
+
+        let breakdown = get_reactions_breakdown(&messages);
+        assert_eq!(breakdown["Participant_2"]["😆"], 301);
+        assert_eq!(breakdown["Participant_2"]["❤"], 291);
+        assert_eq!(breakdown["Participant_1"]["❤"], 138);
+
+        let matrix = get_reactions_matrix(&messages);
+        assert_eq!(matrix["Participant_2"]["Participant_1"], 944);
+        assert_eq!(matrix["Participant_1"]["Participant_2"], 339);
+    }
+
+    #[test]
+    fn test_messenger_activity() {
+        let (messages, _) = parse_facebook(vec![read_to_string(String::from(
+            "./tests/assets/message_1.json",
+        ))
+        .unwrap()]);
+
+        let activity = get_messenger_activity(&messages);
+        assert_eq!(activity.calls["Participant_1"], 9);
+        assert_eq!(activity.missed_calls["Participant_1"], 5);
+        assert_eq!(activity.total_call_duration_s["Participant_1"], 1525);
+        assert_eq!(activity.median_call_duration_s["Participant_1"], 385.5);
+        assert_eq!(activity.calls["Participant_2"], 35);
+        assert_eq!(activity.missed_calls["Participant_2"], 6);
+        assert_eq!(activity.median_call_duration_s["Participant_2"], 111.0);
+        assert_eq!(activity.unsent_rate["Participant_1"], 3.0 / 4300.0);
+        assert_eq!(activity.unsent_rate["Participant_2"], 6.0 / 5219.0);
+        assert!(activity.stickers.is_empty());
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let (messages, _) = parse_facebook(vec![r#"{
+            "participants": [{"name": "p1"}, {"name": "p2"}],
+            "messages": [
+                {"sender_name": "p1", "timestamp_ms": 0, "content": "hello", "type": "Generic"},
+                {"sender_name": "p1", "timestamp_ms": 1, "content": "p1 called you.", "call_duration": 0},
+                {"sender_name": "p2", "timestamp_ms": 2, "content": "p2 unsent a message", "is_unsent": true},
+                {"sender_name": "p2", "timestamp_ms": 3, "content": "p2 added p3 to the group.", "type": "Subscribe"},
+                {"sender_name": "p2", "timestamp_ms": 4, "content": "This message is unavailable", "is_geoblocked_for_viewer": true}
+            ]
+        }"#
+        .to_string()]);
+
+        let contents: Vec<Option<String>> = messages
+            .into_iter()
+            .map(|msg| Into::<BaseMessage>::into(msg).content)
+            .collect();
+        assert_eq!(
+            contents,
+            vec![Some("hello".to_string()), None, None, None, None]
+        );
+    }
 }
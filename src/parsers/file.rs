@@ -1,5 +1,6 @@
+use serde::Serialize;
 use std::collections::HashMap;
-use std::fs::{create_dir_all, File};
+use std::fs::{create_dir_all, write, File};
 use std::path::Path;
 pub fn facebook_file_parser(folder: &String, name: &String) -> Vec<String> {
     let mut correct_paths: Vec<String> = [].to_vec();
@@ -64,3 +65,23 @@ pub fn save_results(
     )
     .expect("Failed to write to msg.json");
 }
+
+/// Write any analysis result as pretty JSON in `output/file_name`.
+pub fn save_json<T: Serialize>(output: &String, file_name: &str, data: &T) {
+    create_dir_all(output).expect("Failed to create output directory");
+
+    let path = format!("{}/{}", output, file_name);
+    serde_json::to_writer_pretty(
+        File::create(&path).unwrap_or_else(|_| panic!("Failed to create {} file", path)),
+        data,
+    )
+    .unwrap_or_else(|_| panic!("Failed to write to {}", path));
+}
+
+/// Write a text export (GraphML, DOT...) in `output/file_name`.
+pub fn save_text(output: &String, file_name: &str, text: &str) {
+    create_dir_all(output).expect("Failed to create output directory");
+
+    let path = format!("{}/{}", output, file_name);
+    write(&path, text).unwrap_or_else(|_| panic!("Failed to write to {}", path));
+}
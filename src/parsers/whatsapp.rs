@@ -5,10 +5,60 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 use super::base::BaseMessage;
+use crate::analysis::filter::MessageFilter;
+use crate::analysis::media::{get_attachment_kind, get_links, MediaKind, SharedMedia};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct WhatsappMessage {
     pub base_message: BaseMessage,
+    /// Name of the attached file, or the `<Media omitted>` marker of exports without media.
+    pub attachment: Option<String>,
+}
+
+impl WhatsappMessage {
+    pub fn matches(&self, filter: &MessageFilter) -> bool {
+        return filter.matches(
+            &self.base_message.sender_name,
+            self.base_message.timestamp_ms,
+            self.base_message.content.as_deref(),
+        );
+    }
+}
+
+/// Split the attachment marker (Android, iOS, English or French exports) from the caption,
+/// which becomes the content.
+fn new_whatsapp_message(
+    name: &str,
+    timestamp_ms: i64,
+    content: &str,
+    attachment_regex: &Regex,
+) -> WhatsappMessage {
+    let (attachment, content) = match attachment_regex.captures(content) {
+        Some(captures) => (
+            captures
+                .name("attached")
+                .or(captures.name("file"))
+                .or(captures.name("omitted"))
+                .map(|attachment| attachment.as_str().to_string()),
+            captures
+                .name("caption")
+                .map_or("", |caption| caption.as_str()),
+        ),
+        None => (None, content),
+    };
+
+    return WhatsappMessage {
+        base_message: BaseMessage {
+            sender_name: name.to_string(),
+            timestamp_ms,
+            content: if content.is_empty() {
+                None
+            } else {
+                Some(content.to_string())
+            },
+        },
+        attachment,
+    };
 }
 
 pub fn parse_whatsapp(texts: Vec<String>) -> (Vec<WhatsappMessage>, HashSet<std::string::String>) {
@@ -16,6 +66,10 @@ pub fn parse_whatsapp(texts: Vec<String>) -> (Vec<WhatsappMessage>, HashSet<std:
         r"^(?P<date>\d{2}\/\d{2}\/\d{4}\,\s\d{2}\:\d{2})\s-\s(?P<name>[\w\s]+)\:\s(?P<message>.+)$",
     )
     .unwrap();
+    let attachment_regex = Regex::new(
+        r"^\u{200e}?(?:<(?:attached|pièce jointe) ?: (?P<attached>[^>]+)>|(?P<file>\S+\.\w+) \((?:file attached|fichier joint)\)|(?P<omitted><(?:Media omitted|Médias omis)>))\s*(?P<caption>.*)$",
+    )
+    .unwrap();
     let mut messages = Vec::<WhatsappMessage>::new();
     let mut name = "";
     let mut timestamp = 0;
@@ -25,13 +79,12 @@ pub fn parse_whatsapp(texts: Vec<String>) -> (Vec<WhatsappMessage>, HashSet<std:
         for msg_line in txt.split("\n") {
             if date_name_header_regex.is_match(msg_line) {
                 if name != "" {
-                    messages.push(WhatsappMessage {
-                        base_message: BaseMessage {
-                            sender_name: name.to_string(),
-                            timestamp_ms: timestamp,
-                            content: Some(content),
-                        },
-                    });
+                    messages.push(new_whatsapp_message(
+                        name,
+                        timestamp,
+                        &content,
+                        &attachment_regex,
+                    ));
                 }
 
                 let captures = date_name_header_regex
@@ -60,13 +113,12 @@ pub fn parse_whatsapp(texts: Vec<String>) -> (Vec<WhatsappMessage>, HashSet<std:
         }
 
         if content != "" {
-            messages.push(WhatsappMessage {
-                base_message: BaseMessage {
-                    sender_name: name.to_string(),
-                    timestamp_ms: timestamp,
-                    content: Some(content.clone()),
-                },
-            });
+            messages.push(new_whatsapp_message(
+                name,
+                timestamp,
+                &content,
+                &attachment_regex,
+            ));
         }
     }
 
@@ -80,3 +132,73 @@ pub fn parse_whatsapp(texts: Vec<String>) -> (Vec<WhatsappMessage>, HashSet<std:
         HashSet::from_iter(messages.iter().map(|m| m.base_message.sender_name.clone()));
     return (messages, participants);
 }
+
+/// Attached files and links of every message.
+pub fn get_whatsapp_media(messages: &Vec<WhatsappMessage>) -> Vec<SharedMedia> {
+    let mut media: Vec<SharedMedia> = Vec::new();
+
+    for msg in messages {
+        let shared_media = |kind: MediaKind, url: Option<String>| SharedMedia {
+            sender_name: msg.base_message.sender_name.clone(),
+            timestamp_ms: msg.base_message.timestamp_ms,
+            kind,
+            url,
+        };
+
+        if let Some(attachment) = &msg.attachment {
+            media.push(shared_media(get_attachment_kind(attachment), None));
+        }
+        if let Some(content) = &msg.base_message.content {
+            for link in get_links(content) {
+                media.push(shared_media(MediaKind::Link, Some(link)));
+            }
+        }
+    }
+
+    return media;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_whatsapp_media, parse_whatsapp};
+    use crate::analysis::media::MediaKind;
+
+    #[test]
+    fn test_whatsapp_attachments() {
+        let export = [
+            "01/02/2024, 10:00 - Alice: IMG-20240201-WA0001.jpg (file attached)",
+            "look at this",
+            "01/02/2024, 10:01 - Bob: <Media omitted>",
+            "01/02/2024, 10:02 - Alice: <attached: 00000012-AUDIO-2024-02-01-10-02-00.opus>",
+            "01/02/2024, 10:03 - Bob: see https://example.com",
+        ]
+        .join("\n");
+
+        let (messages, _) = parse_whatsapp(vec![export]);
+
+        assert_eq!(messages.len(), 4);
+        assert_eq!(
+            messages[0].attachment.as_deref(),
+            Some("IMG-20240201-WA0001.jpg")
+        );
+        assert_eq!(
+            messages[0].base_message.content.as_deref(),
+            Some("look at this")
+        );
+        assert_eq!(messages[1].base_message.content, None);
+
+        let kinds: Vec<MediaKind> = get_whatsapp_media(&messages)
+            .iter()
+            .map(|item| item.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                MediaKind::Photo,
+                MediaKind::Other,
+                MediaKind::Audio,
+                MediaKind::Link
+            ]
+        );
+    }
+}
@@ -1,11 +1,28 @@
+use ratatui::layout::Constraint;
 use ratatui::style::{Color as RatatuiColor, Modifier, Style as RatatuiStyle, Stylize};
+use ratatui::symbols::Marker;
 use ratatui::text::Span;
 use ratatui::widgets::{
-    Bar as RatatuiBar, BarChart as RatatuiBarChart, BarGroup as RatatuiBarGroup,
-    Block as RatatuiBlock, Borders as RatatuiBorders, Paragraph,
+    Axis as RatatuiAxis, Bar as RatatuiBar, BarChart as RatatuiBarChart,
+    BarGroup as RatatuiBarGroup, Block as RatatuiBlock, Borders as RatatuiBorders, Cell, Chart,
+    Dataset, GraphType, Paragraph, Row, Sparkline, Table, Wrap,
 };
 use std::collections::{BTreeMap, HashMap};
 
+use crate::analysis::distinctive::DistinctiveTerms;
+use crate::analysis::emojis::EmojiStats;
+use crate::analysis::heatmap::WEEKDAYS;
+use crate::analysis::media::MediaStats;
+use crate::analysis::milestones::Milestone;
+use crate::analysis::ngrams::PhraseStats;
+use crate::analysis::sentiment::SentimentStats;
+use crate::analysis::stats::{without_outliers, BoxSummary};
+use crate::analysis::style::WritingStyle;
+use crate::analysis::timeline::{format_timestamp, Timeline};
+use crate::analysis::topics::Topics;
+use crate::analysis::turns::TurnTaking;
+use crate::analysis::unanswered::UnansweredStats;
+
 fn get_histogram(data: &HashMap<String, Vec<i64>>, num_buckets: i64) -> RatatuiBarChart<'static> {
     let mut all_values_per_participants: HashMap<String, BTreeMap<i64, i32>> =
         HashMap::from_iter(data.keys().map(|name| {
@@ -137,6 +154,43 @@ pub fn get_reaction_count_plot_cli(
     return bar_chart;
 }
 
+pub fn get_reaction_breakdown_plot_cli(
+    breakdown: &HashMap<String, HashMap<String, i32>>,
+    matrix: &HashMap<String, HashMap<String, i32>>,
+) -> Paragraph<'static> {
+    let mut actors: Vec<&String> = breakdown.keys().collect();
+    actors.sort();
+
+    let mut lines = Vec::new();
+    for actor in actors {
+        let mut reactions: Vec<(&String, &i32)> = breakdown[actor].iter().collect();
+        reactions.sort_by(|a, b| b.1.cmp(a.1));
+        let reactions: Vec<String> = reactions
+            .iter()
+            .map(|(reaction, count)| format!("{} {}", reaction, count))
+            .collect();
+
+        let mut senders: Vec<(&String, &i32)> = matrix
+            .get(actor)
+            .map(|senders| senders.iter().collect())
+            .unwrap_or_default();
+        senders.sort();
+        let senders: Vec<String> = senders
+            .iter()
+            .map(|(sender, count)| format!("{} {}", sender, count))
+            .collect();
+
+        lines.push(format!("{}: {}", actor, reactions.join(", ")));
+        lines.push(format!("    reacted to: {}", senders.join(", ")));
+    }
+
+    return Paragraph::new(lines.join("\n")).block(
+        RatatuiBlock::default()
+            .title("Reactions per type")
+            .borders(RatatuiBorders::ALL),
+    );
+}
+
 pub fn get_hour_plot_cli(hours: &HashMap<String, Vec<i64>>) -> RatatuiBarChart<'static> {
     return get_histogram(hours, 24).block(
         RatatuiBlock::default()
@@ -160,44 +214,87 @@ pub fn get_message_num_plot_cli(num: &HashMap<String, Vec<i64>>) -> RatatuiBarCh
     );
 }
 
-pub fn get_response_time_plot_cli(
-    responses_time: &HashMap<String, Vec<i64>>,
-) -> RatatuiBarChart<'static> {
-    // TODO : EXTRACT ME TO BE USED IN WEB TOO
-    fn percentile_of_sorted(sorted_samples: &[i64], pct: f64) -> f64 {
-        assert!(!sorted_samples.is_empty());
-        if sorted_samples.len() == 1 {
-            return sorted_samples[0] as f64;
-        }
-        let zero: f64 = 0.0;
-        assert!(zero <= pct);
-        let hundred = 100_f64;
-        assert!(pct <= hundred);
-        if pct == hundred {
-            return sorted_samples[sorted_samples.len() - 1] as f64;
-        }
-        let length = sorted_samples.len() - 1;
-        let rank = (pct / hundred) * length as f64;
-        let left_rank = rank.floor();
-        let d = rank - left_rank;
-        let n = left_rank as usize;
-        let lo = sorted_samples[n] as f64;
-        let hi = sorted_samples[n + 1] as f64;
-        lo + (hi - lo) * d
-    }
+pub fn get_turn_taking_stats_cli(turns: &TurnTaking) -> Paragraph<'static> {
+    let mut names: Vec<&String> = turns.runs.keys().collect();
+    names.sort();
+
+    let mut lines: Vec<String> = names
+        .iter()
+        .map(|name| {
+            format!(
+                "{}: double texting {:.1}%, triple texting {:.1}%",
+                name,
+                turns.double_text_rate[*name] * 100.0,
+                turns.triple_text_rate[*name] * 100.0,
+            )
+        })
+        .collect();
+    lines.push(format!(
+        "Average turns per session: {:.1}",
+        turns.turns_per_session
+    ));
 
-    let mut filtered_response_times = HashMap::new();
-    for (name, times) in responses_time.iter() {
-        let ninety_percentile = percentile_of_sorted(times, 90.0);
-        let filtered_response_time: Vec<i64> = times
+    return Paragraph::new(lines.join("\n")).block(
+        RatatuiBlock::default()
+            .title("Turn taking")
+            .borders(RatatuiBorders::ALL),
+    );
+}
+
+pub fn get_unanswered_stats_cli(unanswered: &UnansweredStats) -> Paragraph<'static> {
+    let mut names: Vec<&String> = unanswered.unanswered_rate.keys().collect();
+    names.sort();
+
+    let mut lines: Vec<String> = names
+        .iter()
+        .map(|name| {
+            format!(
+                "{}: {} unanswered bursts ({:.1}%), {} questions, {} answered",
+                name,
+                unanswered.unanswered_bursts[*name],
+                unanswered.unanswered_rate[*name] * 100.0,
+                unanswered.questions[*name],
+                match unanswered.question_answer_rate.get(*name) {
+                    Some(rate) => format!("{:.1}%", rate * 100.0),
+                    None => "none".to_string(),
+                },
+            )
+        })
+        .collect();
+
+    lines.push(String::new());
+    lines.push("Longest silences:".to_string());
+    for silence in unanswered.longest_silences.iter() {
+        let content: String = silence
+            .content
             .clone()
-            .into_iter()
-            .filter(|x| x < &&(ninety_percentile as i64))
+            .unwrap_or_default()
+            .chars()
+            .take(60)
             .collect();
-
-        filtered_response_times.insert(name.clone(), filtered_response_time);
+        lines.push(format!(
+            "  {:.1} days after {}: \"{}\", broken by {}",
+            silence.duration_ms as f64 / (24.0 * 60.0 * 60.0 * 1000.0),
+            silence.sender_name,
+            content,
+            silence.broken_by,
+        ));
     }
-    // TODO : END TODO
+
+    return Paragraph::new(lines.join("\n")).block(
+        RatatuiBlock::default()
+            .title("Unanswered messages")
+            .borders(RatatuiBorders::ALL),
+    );
+}
+
+pub fn get_response_time_plot_cli(
+    responses_time: &HashMap<String, Vec<i64>>,
+) -> RatatuiBarChart<'static> {
+    let filtered_response_times: HashMap<String, Vec<i64>> = responses_time
+        .iter()
+        .map(|(name, times)| (name.clone(), without_outliers(times)))
+        .collect();
 
     return get_histogram(&filtered_response_times, 15).block(
         RatatuiBlock::default()
@@ -206,8 +303,36 @@ pub fn get_response_time_plot_cli(
     );
 }
 
-pub fn get_word_plot_cli(words: &HashMap<String, Vec<String>>) -> Vec<Paragraph> {
-    let mut paragraphs: Vec<Paragraph> = Vec::new();
+pub fn get_response_time_stats_cli(summaries: &HashMap<String, BoxSummary>) -> Paragraph<'static> {
+    let mut names: Vec<&String> = summaries.keys().collect();
+    names.sort();
+
+    let lines: Vec<String> = names
+        .iter()
+        .map(|name| {
+            let summary = &summaries[*name];
+            format!(
+                "{}: median {:.0}s, quartiles {:.0}s-{:.0}s, trimmed mean {:.0}s, {} outliers out of {}",
+                name,
+                summary.median,
+                summary.q1,
+                summary.q3,
+                summary.trimmed_mean,
+                summary.num_outliers,
+                summary.count,
+            )
+        })
+        .collect();
+
+    return Paragraph::new(lines.join("\n")).block(
+        RatatuiBlock::default()
+            .title("Response time within sessions")
+            .borders(RatatuiBorders::ALL),
+    );
+}
+
+pub fn get_word_plot_cli(words: &HashMap<String, Vec<String>>) -> Vec<Paragraph<'static>> {
+    let mut paragraphs: Vec<Paragraph<'static>> = Vec::new();
 
     let create_block = |title: &String| {
         RatatuiBlock::default()
@@ -224,3 +349,507 @@ pub fn get_word_plot_cli(words: &HashMap<String, Vec<String>>) -> Vec<Paragraph>
     }
     return paragraphs;
 }
+
+pub fn get_distinctive_word_plot_cli(
+    words: &DistinctiveTerms,
+    bigrams: &DistinctiveTerms,
+) -> Vec<Paragraph<'static>> {
+    let mut paragraphs: Vec<Paragraph> = Vec::new();
+
+    let format_terms = |terms: &Vec<(String, f64)>| -> Vec<String> {
+        return terms
+            .iter()
+            .map(|(term, score)| format!("{} ({:.1})", term, score))
+            .collect();
+    };
+
+    let mut names: Vec<&String> = words.log_odds.keys().collect();
+    names.sort();
+
+    for name in names {
+        let mut lines = format_terms(&words.log_odds[name]);
+        if let Some(participant_bigrams) = bigrams.log_odds.get(name) {
+            lines.push(String::new());
+            lines.extend(format_terms(participant_bigrams));
+        }
+
+        paragraphs.push(
+            Paragraph::new(lines.join("\n")).block(
+                RatatuiBlock::default()
+                    .borders(RatatuiBorders::ALL)
+                    .style(RatatuiStyle::default().fg(RatatuiColor::Gray))
+                    .title(Span::styled(
+                        format!("{} distinctive", name),
+                        RatatuiStyle::default().add_modifier(Modifier::BOLD),
+                    )),
+            ),
+        );
+    }
+    return paragraphs;
+}
+
+pub fn get_emoji_plot_cli(emojis: &EmojiStats) -> Vec<Paragraph<'static>> {
+    let mut paragraphs: Vec<Paragraph<'static>> = Vec::new();
+
+    let mut names: Vec<&String> = emojis.top_emojis.keys().collect();
+    names.sort();
+
+    for name in names {
+        let top_emojis = &emojis.top_emojis[name];
+        let mut lines = vec![format!(
+            "{:.2} emojis per message",
+            emojis.emojis_per_message[name]
+        )];
+        lines.extend(
+            top_emojis
+                .iter()
+                .map(|(emoji, count)| format!("{} {}", emoji, count)),
+        );
+
+        paragraphs.push(
+            Paragraph::new(lines.join("\n")).block(
+                RatatuiBlock::default()
+                    .borders(RatatuiBorders::ALL)
+                    .style(RatatuiStyle::default().fg(RatatuiColor::Gray))
+                    .title(Span::styled(
+                        name.clone(),
+                        RatatuiStyle::default().add_modifier(Modifier::BOLD),
+                    )),
+            ),
+        );
+    }
+    return paragraphs;
+}
+
+/// One paragraph per participant with the number of items of each kind (and their share of
+/// all the items of that kind), then the most shared domains.
+pub fn get_media_plot_cli(media: &MediaStats) -> Vec<Paragraph<'static>> {
+    let mut paragraphs: Vec<Paragraph> = Vec::new();
+
+    let mut names: Vec<&String> = media.counts.keys().collect();
+    names.sort();
+
+    for name in names {
+        let lines: Vec<String> = media.counts[name]
+            .iter()
+            .map(|(kind, count)| {
+                format!(
+                    "{} {} ({:.0}%)",
+                    kind,
+                    count,
+                    media.shares[name][kind] * 100.0
+                )
+            })
+            .collect();
+
+        paragraphs.push(
+            Paragraph::new(lines.join("\n")).block(
+                RatatuiBlock::default()
+                    .borders(RatatuiBorders::ALL)
+                    .style(RatatuiStyle::default().fg(RatatuiColor::Gray))
+                    .title(Span::styled(
+                        name.clone(),
+                        RatatuiStyle::default().add_modifier(Modifier::BOLD),
+                    )),
+            ),
+        );
+    }
+
+    let domains: Vec<String> = media
+        .top_domains
+        .iter()
+        .map(|(domain, count)| format!("{} {}", domain, count))
+        .collect();
+    paragraphs.push(
+        Paragraph::new(domains.join("\n")).block(
+            RatatuiBlock::default()
+                .borders(RatatuiBorders::ALL)
+                .title("Top domains"),
+        ),
+    );
+
+    return paragraphs;
+}
+
+pub fn get_phrase_plot_cli(phrases: &PhraseStats) -> Vec<Paragraph<'static>> {
+    let mut paragraphs: Vec<Paragraph> = Vec::new();
+
+    let format_counts = |title: &str, counts: Option<&Vec<(String, i64)>>| -> Vec<String> {
+        let mut lines = vec![title.to_string()];
+        for (phrase, count) in counts.into_iter().flatten() {
+            lines.push(format!("  {} ({})", phrase, count));
+        }
+        return lines;
+    };
+
+    let mut names: Vec<&String> = phrases.bigrams.per_participant.keys().collect();
+    names.sort();
+
+    for name in names {
+        let mut lines = format_counts("Bigrams", phrases.bigrams.per_participant.get(name));
+        lines.extend(format_counts(
+            "Trigrams",
+            phrases.trigrams.per_participant.get(name),
+        ));
+        lines.push("Collocations".to_string());
+        for collocation in phrases
+            .collocations
+            .per_participant
+            .get(name)
+            .into_iter()
+            .flatten()
+        {
+            lines.push(format!(
+                "  {} ({}, pmi {:.1})",
+                collocation.bigram, collocation.count, collocation.pmi
+            ));
+        }
+        lines.extend(format_counts(
+            "Catchphrases",
+            phrases.catchphrases.get(name),
+        ));
+
+        paragraphs.push(
+            Paragraph::new(lines.join("\n")).block(
+                RatatuiBlock::default()
+                    .borders(RatatuiBorders::ALL)
+                    .style(RatatuiStyle::default().fg(RatatuiColor::Gray))
+                    .title(Span::styled(
+                        name.clone(),
+                        RatatuiStyle::default().add_modifier(Modifier::BOLD),
+                    )),
+            ),
+        );
+    }
+    return paragraphs;
+}
+
+/// Sentiment series to draw as sparklines, with their title: the weekly sentiment of each
+/// participant then the sentiment of every session. Scores (-1 to 1) are scaled to 0-100.
+pub fn get_sentiment_series_cli(sentiment: &SentimentStats) -> Vec<(String, Vec<u64>)> {
+    let to_bar = |score: &f64| ((score + 1.0) * 50.0).round() as u64;
+
+    let mut names: Vec<&String> = sentiment.per_week.keys().collect();
+    names.sort();
+
+    let mut series: Vec<(String, Vec<u64>)> = names
+        .into_iter()
+        .map(|name| {
+            (
+                format!(
+                    "{} weekly sentiment (mean {:.2})",
+                    name,
+                    sentiment.per_participant.get(name).cloned().unwrap_or(0.0)
+                ),
+                sentiment.per_week[name].values().map(to_bar).collect(),
+            )
+        })
+        .collect();
+    series.push((
+        "Sentiment per session".to_string(),
+        sentiment
+            .per_session
+            .iter()
+            .map(|(_, score)| to_bar(score))
+            .collect(),
+    ));
+
+    return series;
+}
+
+pub fn get_sentiment_sparkline_cli<'a>(title: &'a str, data: &'a [u64]) -> Sparkline<'a> {
+    return Sparkline::default()
+        .block(
+            RatatuiBlock::default()
+                .title(title)
+                .borders(RatatuiBorders::ALL),
+        )
+        .data(data)
+        .max(100)
+        .style(RatatuiStyle::default().fg(RatatuiColor::Yellow));
+}
+
+pub fn get_topic_plot_cli(topics: &Topics) -> Vec<Paragraph<'static>> {
+    let topic_lines: Vec<String> = topics
+        .terms
+        .iter()
+        .enumerate()
+        .map(|(k, terms)| {
+            format!(
+                "Topic {}: {}",
+                k + 1,
+                terms
+                    .iter()
+                    .map(|(term, _)| term.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            )
+        })
+        .collect();
+
+    let mut names: Vec<&String> = topics.per_participant.keys().collect();
+    names.sort();
+    let mut share_lines: Vec<String> = Vec::new();
+    for name in names {
+        share_lines.push(name.clone());
+        for (k, share) in topics.per_participant[name].iter().enumerate() {
+            share_lines.push(format!(
+                "  {} ({}): {:.1}%",
+                k + 1,
+                topics.label(k),
+                share * 100.0
+            ));
+        }
+    }
+
+    return [
+        ("Topics", topic_lines),
+        ("Topics per participant", share_lines),
+    ]
+    .into_iter()
+    .map(|(title, lines)| {
+        Paragraph::new(lines.join("\n")).block(
+            RatatuiBlock::default()
+                .borders(RatatuiBorders::ALL)
+                .style(RatatuiStyle::default().fg(RatatuiColor::Gray))
+                .title(Span::styled(
+                    title,
+                    RatatuiStyle::default().add_modifier(Modifier::BOLD),
+                )),
+        )
+    })
+    .collect();
+}
+
+/// Generated messages of each participant, one column per participant.
+pub fn get_generated_messages_cli(
+    messages: &HashMap<String, Vec<String>>,
+) -> Vec<Paragraph<'static>> {
+    let mut names: Vec<&String> = messages.keys().collect();
+    names.sort();
+
+    return names
+        .into_iter()
+        .map(|name| {
+            let lines: Vec<String> = messages[name]
+                .iter()
+                .map(|text| format!("- {}", text))
+                .collect();
+            Paragraph::new(lines.join("\n"))
+                .wrap(Wrap { trim: true })
+                .block(
+                    RatatuiBlock::default()
+                        .borders(RatatuiBorders::ALL)
+                        .style(RatatuiStyle::default().fg(RatatuiColor::Gray))
+                        .title(Span::styled(
+                            format!("Talk like {} (g to generate again)", name),
+                            RatatuiStyle::default().add_modifier(Modifier::BOLD),
+                        )),
+                )
+        })
+        .collect();
+}
+
+/// One line per milestone, the date then what happened and the message marking it.
+pub fn get_milestones_cli(milestones: &Vec<Milestone>) -> Paragraph<'static> {
+    let lines: Vec<String> = milestones
+        .iter()
+        .map(|milestone| {
+            let date = format_timestamp(milestone.timestamp_ms);
+            format!(
+                "{}  {}\n                  {}: {}",
+                date,
+                milestone.title,
+                milestone.sender_name,
+                milestone.content.clone().unwrap_or_default()
+            )
+        })
+        .collect();
+
+    return Paragraph::new(lines.join("\n"))
+        .wrap(Wrap { trim: false })
+        .block(
+            RatatuiBlock::default()
+                .borders(RatatuiBorders::ALL)
+                .style(RatatuiStyle::default().fg(RatatuiColor::Gray))
+                .title(Span::styled(
+                    "Milestones",
+                    RatatuiStyle::default().add_modifier(Modifier::BOLD),
+                )),
+        );
+}
+
+/// Rolling average of every participant as chart points (bucket index, messages).
+pub fn get_timeline_points_cli(timeline: &Timeline) -> Vec<(String, Vec<(f64, f64)>)> {
+    let mut names: Vec<&String> = timeline.rolling_average.keys().collect();
+    names.sort();
+
+    return names
+        .into_iter()
+        .map(|name| {
+            (
+                name.clone(),
+                timeline.rolling_average[name]
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, average)| (idx as f64, *average))
+                    .collect(),
+            )
+        })
+        .collect();
+}
+
+pub fn get_timeline_chart_cli<'a>(
+    timeline: &Timeline,
+    points: &'a [(String, Vec<(f64, f64)>)],
+) -> Chart<'a> {
+    let colors = [
+        RatatuiColor::Yellow,
+        RatatuiColor::Cyan,
+        RatatuiColor::Magenta,
+        RatatuiColor::Green,
+        RatatuiColor::Red,
+        RatatuiColor::Blue,
+    ];
+    let datasets: Vec<Dataset> = points
+        .iter()
+        .enumerate()
+        .map(|(i, (name, data))| {
+            Dataset::default()
+                .name(name.clone())
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(RatatuiStyle::default().fg(colors[i % colors.len()]))
+                .data(data)
+        })
+        .collect();
+
+    let max_count = points
+        .iter()
+        .flat_map(|(_, data)| data.iter().map(|(_, average)| *average))
+        .fold(0.0, f64::max)
+        .ceil()
+        .max(1.0);
+    let num_buckets = timeline.buckets.len();
+    let x_labels: Vec<Span> = [
+        timeline.buckets.first(),
+        timeline.buckets.get(num_buckets / 2),
+        timeline.buckets.last(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|bucket| Span::raw(bucket.clone()))
+    .collect();
+
+    return Chart::new(datasets)
+        .block(
+            RatatuiBlock::default()
+                .title(format!(
+                    "Messages per {} ({} {}s rolling average)",
+                    timeline.granularity.name(),
+                    timeline.window,
+                    timeline.granularity.name()
+                ))
+                .borders(RatatuiBorders::ALL),
+        )
+        .x_axis(
+            RatatuiAxis::default()
+                .bounds([0.0, num_buckets.saturating_sub(1).max(1) as f64])
+                .labels(x_labels),
+        )
+        .y_axis(RatatuiAxis::default().bounds([0.0, max_count]).labels(vec![
+            Span::raw("0"),
+            Span::raw(format!("{:.0}", max_count / 2.0)),
+            Span::raw(format!("{:.0}", max_count)),
+        ]));
+}
+
+/// One column per participant, one row per writing style metric.
+pub fn get_style_table_cli(styles: &HashMap<String, WritingStyle>) -> Table<'static> {
+    let mut names: Vec<&String> = styles.keys().collect();
+    names.sort();
+
+    let header = Row::new(
+        std::iter::once(Cell::from(""))
+            .chain(names.iter().map(|name| Cell::from(name.to_string())))
+            .collect::<Vec<Cell>>(),
+    )
+    .style(RatatuiStyle::default().add_modifier(Modifier::BOLD));
+    let columns: Vec<Vec<(&str, String)>> =
+        names.iter().map(|name| styles[*name].metrics()).collect();
+    let rows: Vec<Row> = WritingStyle::default()
+        .metrics()
+        .iter()
+        .enumerate()
+        .map(|(idx, (label, _))| {
+            Row::new(
+                std::iter::once(Cell::from(label.to_string()))
+                    .chain(
+                        columns
+                            .iter()
+                            .map(|metrics| Cell::from(metrics[idx].1.clone())),
+                    )
+                    .collect::<Vec<Cell>>(),
+            )
+        })
+        .collect();
+
+    let widths: Vec<Constraint> = std::iter::once(Constraint::Length(24))
+        .chain(names.iter().map(|_| Constraint::Length(20)))
+        .collect();
+
+    return Table::new(rows, widths).header(header).block(
+        RatatuiBlock::default()
+            .title("Writing style")
+            .borders(RatatuiBorders::ALL),
+    );
+}
+
+/// Weekday × hour grid, the brighter the cell the more messages per week.
+pub fn get_activity_heatmap_cli(title: String, grid: &[Vec<f64>]) -> Table<'static> {
+    let max_count = grid.iter().flatten().cloned().fold(0.0, f64::max);
+
+    let header = Row::new(
+        std::iter::once(Cell::from(""))
+            .chain((0..24).map(|hour| Cell::from(format!("{:>2}", hour))))
+            .collect::<Vec<Cell>>(),
+    );
+    let rows: Vec<Row> = grid
+        .iter()
+        .zip(WEEKDAYS.iter())
+        .map(|(hours, weekday)| {
+            Row::new(
+                std::iter::once(Cell::from(weekday.to_string()))
+                    .chain(hours.iter().map(|count| {
+                        let intensity = if max_count > 0.0 {
+                            (count / max_count * 255.0).round() as u8
+                        } else {
+                            0
+                        };
+                        Cell::from("").style(RatatuiStyle::default().bg(RatatuiColor::Rgb(
+                            intensity / 4,
+                            intensity,
+                            intensity / 2,
+                        )))
+                    }))
+                    .collect::<Vec<Cell>>(),
+            )
+        })
+        .collect();
+
+    let widths: Vec<Constraint> = std::iter::once(Constraint::Length(4))
+        .chain(std::iter::repeat_n(Constraint::Length(2), 24))
+        .collect();
+
+    return Table::new(rows, widths)
+        .header(header)
+        .column_spacing(1)
+        .block(
+            RatatuiBlock::default()
+                .title(format!(
+                    "{} (max {:.1} messages per week)",
+                    title, max_count
+                ))
+                .borders(RatatuiBorders::ALL),
+        );
+}
@@ -1,7 +1,20 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
-use plotly::common::Title;
-use plotly::{Bar, Histogram, Layout, Plot};
+use plotly::common::{DashType, Fill, Line, LineShape, Marker, Mode, Title};
+use plotly::layout::{Axis, AxisType, BarMode, GridPattern, LayoutGrid, RangeSlider};
+use plotly::{Bar, HeatMap, Histogram, Layout, Plot, Scatter};
+
+use crate::analysis::emojis::EmojiStats;
+use crate::analysis::graph::{get_force_layout, InteractionGraph};
+use crate::analysis::heatmap::{ActivityHeatmap, WEEKDAYS};
+use crate::analysis::media::{MediaKind, MediaStats};
+use crate::analysis::reacted::ReactionRate;
+use crate::analysis::sentiment::SentimentStats;
+use crate::analysis::stats::without_outliers;
+use crate::analysis::survival::{SurvivalCurve, SurvivalPoint};
+use crate::analysis::timeline::Timeline;
+use crate::analysis::topics::Topics;
+use crate::analysis::tracking::TermTracking;
 pub fn get_message_count_plot(messages_count: &HashMap<String, i32>) -> Plot {
     let mut msg_plot = Plot::new();
 
@@ -41,6 +54,471 @@ pub fn get_reaction_count_plot(reactions_count: &HashMap<String, i32>) -> Plot {
     return reaction_plot;
 }
 
+pub fn get_reaction_breakdown_plot(breakdown: &HashMap<String, HashMap<String, i32>>) -> Plot {
+    let mut breakdown_plot = Plot::new();
+
+    let reactions: Vec<String> = breakdown
+        .values()
+        .flat_map(|counts| counts.keys().cloned())
+        .collect::<BTreeSet<String>>()
+        .into_iter()
+        .collect();
+
+    for (name, counts) in breakdown.iter() {
+        breakdown_plot.add_trace(
+            Bar::new(
+                reactions.clone(),
+                reactions
+                    .iter()
+                    .map(|reaction| *counts.get(reaction).unwrap_or(&0))
+                    .collect(),
+            )
+            .name(name),
+        )
+    }
+
+    let breakdown_layout = Layout::new().title(Title::new("Reactions given per type."));
+
+    breakdown_plot.set_layout(breakdown_layout);
+
+    return breakdown_plot;
+}
+
+pub fn get_reaction_matrix_plot(matrix: &HashMap<String, HashMap<String, i32>>) -> Plot {
+    let mut matrix_plot = Plot::new();
+
+    for (actor, senders) in matrix.iter() {
+        let mut senders: Vec<(&String, &i32)> = senders.iter().collect();
+        senders.sort();
+
+        matrix_plot.add_trace(
+            Bar::new(
+                senders.iter().map(|x| x.0.clone()).collect(),
+                senders.iter().map(|x| *x.1).collect(),
+            )
+            .name(actor),
+        )
+    }
+
+    let matrix_layout = Layout::new().title(Title::new("Who reacts to whose messages."));
+
+    matrix_plot.set_layout(matrix_layout);
+
+    return matrix_plot;
+}
+
+/// Share of the messages of each group (length, hour...) that got at least one reaction.
+pub fn get_reaction_rate_plot(rates: &Vec<ReactionRate>, title: &str) -> Plot {
+    let mut rate_plot = Plot::new();
+
+    rate_plot.add_trace(
+        Bar::new(
+            rates.iter().map(|x| x.label.clone()).collect(),
+            rates.iter().map(|x| x.rate * 100.0).collect(),
+        )
+        .name("Reacted messages (%)"),
+    );
+
+    let rate_layout = Layout::new().title(Title::new(title));
+
+    rate_plot.set_layout(rate_layout);
+
+    return rate_plot;
+}
+
+pub fn get_reaction_reciprocity_plot(reciprocity: &HashMap<String, HashMap<String, f64>>) -> Plot {
+    let mut reciprocity_plot = Plot::new();
+
+    for (actor, senders) in reciprocity.iter() {
+        let mut senders: Vec<(&String, &f64)> = senders.iter().collect();
+        senders.sort_by(|a, b| a.0.cmp(b.0));
+
+        reciprocity_plot.add_trace(
+            Bar::new(
+                senders.iter().map(|x| x.0.clone()).collect(),
+                senders.iter().map(|x| x.1 * 100.0).collect(),
+            )
+            .name(actor),
+        )
+    }
+
+    let reciprocity_layout = Layout::new().title(Title::new(
+        "Share of the messages of each sender reacted to (%).",
+    ));
+
+    reciprocity_plot.set_layout(reciprocity_layout);
+
+    return reciprocity_plot;
+}
+
+/// Force layout of the interaction graph: the node size follows the number of messages and
+/// the edge width the number of replies and mentions.
+pub fn get_interaction_graph_plot(graph: &InteractionGraph) -> Plot {
+    let mut graph_plot = Plot::new();
+    let positions = get_force_layout(graph);
+    let position_of = |name: &String| {
+        let idx = graph
+            .nodes
+            .iter()
+            .position(|node| &node.name == name)
+            .expect("Unknown participant");
+        positions[idx]
+    };
+    let max_weight = graph.edges.iter().map(|x| x.weight()).max().unwrap_or(1);
+    let max_messages = graph
+        .nodes
+        .iter()
+        .map(|x| x.messages)
+        .max()
+        .unwrap_or(1)
+        .max(1);
+
+    for edge in graph.edges.iter() {
+        let (source, target) = (position_of(&edge.source), position_of(&edge.target));
+        graph_plot.add_trace(
+            Scatter::new(vec![source.0, target.0], vec![source.1, target.1])
+                .mode(Mode::Lines)
+                .line(Line::new().width(1.0 + 7.0 * edge.weight() as f64 / max_weight as f64))
+                .name(&format!(
+                    "{} → {}: {}",
+                    edge.source,
+                    edge.target,
+                    edge.weight()
+                ))
+                .show_legend(false),
+        )
+    }
+    for (node, position) in graph.nodes.iter().zip(positions.iter()) {
+        graph_plot.add_trace(
+            Scatter::new(vec![position.0], vec![position.1])
+                .mode(Mode::MarkersText)
+                .text(&node.name)
+                .marker(Marker::new().size(10 + (40 * node.messages / max_messages) as usize))
+                .name(&node.name),
+        )
+    }
+
+    let hidden_axis = Axis::new()
+        .show_grid(false)
+        .zero_line(false)
+        .show_tick_labels(false);
+    let graph_layout = Layout::new()
+        .title(Title::new("Who answers whom."))
+        .x_axis(hidden_axis.clone())
+        .y_axis(hidden_axis);
+
+    graph_plot.set_layout(graph_layout);
+
+    return graph_plot;
+}
+
+pub fn get_emoji_plot(emojis: &EmojiStats) -> Plot {
+    let mut emoji_plot = Plot::new();
+
+    for (name, top_emojis) in emojis.top_emojis.iter() {
+        emoji_plot.add_trace(
+            Bar::new(
+                top_emojis.iter().map(|x| x.0.clone()).collect(),
+                top_emojis.iter().map(|x| x.1).collect(),
+            )
+            .name(&format!(
+                "{} ({:.2} per message)",
+                name, emojis.emojis_per_message[name]
+            )),
+        )
+    }
+
+    let emoji_layout = Layout::new().title(Title::new("Most used emojis."));
+
+    emoji_plot.set_layout(emoji_layout);
+
+    return emoji_plot;
+}
+
+pub fn get_emoji_timeline_plot(emojis: &EmojiStats) -> Plot {
+    let mut emoji_timeline_plot = Plot::new();
+
+    for (name, months) in emojis.usage_over_time.iter() {
+        emoji_timeline_plot.add_trace(
+            Scatter::new(
+                months.keys().cloned().collect(),
+                months.values().cloned().collect(),
+            )
+            .mode(Mode::LinesMarkers)
+            .name(name),
+        )
+    }
+
+    let emoji_timeline_layout = Layout::new().title(Title::new("Emojis sent per month."));
+
+    emoji_timeline_plot.set_layout(emoji_timeline_layout);
+
+    return emoji_timeline_plot;
+}
+
+pub fn get_media_plot(media: &MediaStats) -> Plot {
+    let mut media_plot = Plot::new();
+
+    let kinds: Vec<String> = MediaKind::ALL
+        .iter()
+        .map(|kind| kind.name().to_string())
+        .filter(|kind| {
+            media
+                .counts
+                .values()
+                .any(|counts| counts.contains_key(kind))
+        })
+        .collect();
+
+    for (name, counts) in media.counts.iter() {
+        media_plot.add_trace(
+            Bar::new(
+                kinds.clone(),
+                kinds
+                    .iter()
+                    .map(|kind| *counts.get(kind).unwrap_or(&0))
+                    .collect(),
+            )
+            .name(name),
+        )
+    }
+
+    let media_layout = Layout::new().title(Title::new("Media shared per participant."));
+
+    media_plot.set_layout(media_layout);
+
+    return media_plot;
+}
+
+pub fn get_media_timeline_plot(media: &MediaStats) -> Plot {
+    let mut media_timeline_plot = Plot::new();
+
+    for (kind, months) in media.over_time.iter() {
+        media_timeline_plot.add_trace(
+            Scatter::new(
+                months.keys().cloned().collect(),
+                months.values().cloned().collect(),
+            )
+            .mode(Mode::LinesMarkers)
+            .name(kind),
+        )
+    }
+
+    let media_timeline_layout = Layout::new().title(Title::new("Media shared per month."));
+
+    media_timeline_plot.set_layout(media_timeline_layout);
+
+    return media_timeline_plot;
+}
+
+pub fn get_domain_plot(media: &MediaStats) -> Plot {
+    let mut domain_plot = Plot::new();
+
+    domain_plot.add_trace(
+        Bar::new(
+            media.top_domains.iter().map(|x| x.0.clone()).collect(),
+            media.top_domains.iter().map(|x| x.1).collect(),
+        )
+        .name("Links"),
+    );
+
+    let domain_layout = Layout::new().title(Title::new("Most shared websites."));
+
+    domain_plot.set_layout(domain_layout);
+
+    return domain_plot;
+}
+
+pub fn get_sentiment_plot(sentiment: &SentimentStats) -> Plot {
+    let mut sentiment_plot = Plot::new();
+
+    for (name, weeks) in sentiment.per_week.iter() {
+        sentiment_plot.add_trace(
+            Scatter::new(
+                weeks.keys().cloned().collect(),
+                weeks.values().cloned().collect(),
+            )
+            .mode(Mode::LinesMarkers)
+            .name(name),
+        )
+    }
+
+    let sentiment_layout = Layout::new()
+        .title(Title::new(
+            "Average sentiment per week (-1 negative, 1 positive).",
+        ))
+        .y_axis(Axis::new().range(vec![-1.0, 1.0]));
+
+    sentiment_plot.set_layout(sentiment_layout);
+
+    return sentiment_plot;
+}
+
+pub fn get_topic_plot(topics: &Topics) -> Plot {
+    let mut topic_plot = Plot::new();
+
+    for k in 0..topics.terms.len() {
+        topic_plot.add_trace(
+            Scatter::new(
+                topics.over_time.keys().cloned().collect(),
+                topics.over_time.values().map(|shares| shares[k]).collect(),
+            )
+            .mode(Mode::Lines)
+            .stack_group("topics")
+            .name(&topics.label(k)),
+        )
+    }
+
+    let topic_layout = Layout::new().title(Title::new("Share of each topic per month."));
+
+    topic_plot.set_layout(topic_layout);
+
+    return topic_plot;
+}
+
+pub fn get_topic_participant_plot(topics: &Topics) -> Plot {
+    let mut topic_participant_plot = Plot::new();
+    let mut names: Vec<&String> = topics.per_participant.keys().collect();
+    names.sort();
+
+    for k in 0..topics.terms.len() {
+        topic_participant_plot.add_trace(
+            Bar::new(
+                names.iter().map(|name| name.to_string()).collect(),
+                names
+                    .iter()
+                    .map(|name| topics.per_participant[*name][k])
+                    .collect(),
+            )
+            .name(&topics.label(k)),
+        )
+    }
+
+    let topic_participant_layout = Layout::new()
+        .title(Title::new("Share of each topic per participant."))
+        .bar_mode(BarMode::Stack);
+
+    topic_participant_plot.set_layout(topic_participant_layout);
+
+    return topic_participant_plot;
+}
+
+pub fn get_timeline_plot(timeline: &Timeline) -> Plot {
+    let mut timeline_plot = Plot::new();
+    let mut names: Vec<&String> = timeline.counts.keys().collect();
+    names.sort();
+
+    for name in names {
+        timeline_plot.add_trace(
+            Scatter::new(timeline.buckets.clone(), timeline.counts[name].clone())
+                .mode(Mode::Lines)
+                .name(name),
+        );
+        timeline_plot.add_trace(
+            Scatter::new(
+                timeline.buckets.clone(),
+                timeline.rolling_average[name].clone(),
+            )
+            .mode(Mode::Lines)
+            .line(Line::new().dash(DashType::Dash))
+            .name(&format!(
+                "{} ({} {}s average)",
+                name,
+                timeline.window,
+                timeline.granularity.name()
+            )),
+        );
+    }
+
+    let timeline_layout = Layout::new()
+        .title(Title::new(&format!(
+            "Messages per {}.",
+            timeline.granularity.name()
+        )))
+        .x_axis(Axis::new().range_slider(RangeSlider::new().visible(true)));
+
+    timeline_plot.set_layout(timeline_layout);
+
+    return timeline_plot;
+}
+
+/// Uses of every tracked term per bucket, one line per term and participant.
+pub fn get_term_tracking_plot(tracking: &TermTracking) -> Plot {
+    let mut tracking_plot = Plot::new();
+
+    for usage in tracking.terms.iter() {
+        let mut names: Vec<&String> = usage.counts.keys().collect();
+        names.sort();
+
+        for name in names {
+            tracking_plot.add_trace(
+                Scatter::new(tracking.buckets.clone(), usage.counts[name].clone())
+                    .mode(Mode::LinesMarkers)
+                    .name(&format!("{} ({})", usage.term, name)),
+            )
+        }
+    }
+
+    let tracking_layout = Layout::new()
+        .title(Title::new(&format!(
+            "Uses per {}.",
+            tracking.granularity.name()
+        )))
+        .x_axis(Axis::new().range_slider(RangeSlider::new().visible(true)));
+
+    tracking_plot.set_layout(tracking_layout);
+
+    return tracking_plot;
+}
+
+/// Combined heatmap on top, then one heatmap per participant.
+pub fn get_activity_heatmap_plot(heatmap: &ActivityHeatmap) -> Plot {
+    let mut heatmap_plot = Plot::new();
+    let mut names: Vec<&String> = heatmap.per_participant.keys().collect();
+    names.sort();
+
+    let mut grids: Vec<(String, &Vec<Vec<f64>>)> = vec![("All".to_string(), &heatmap.combined)];
+    for name in names {
+        grids.push((name.clone(), &heatmap.per_participant[name]));
+    }
+
+    for (i, (name, grid)) in grids.iter().enumerate() {
+        let axis_suffix = if i == 0 {
+            String::new()
+        } else {
+            (i + 1).to_string()
+        };
+        heatmap_plot.add_trace(
+            HeatMap::new((0..24).collect(), WEEKDAYS.to_vec(), grid.to_vec())
+                .name(name)
+                .x_axis(&format!("x{}", axis_suffix))
+                .y_axis(&format!("y{}", axis_suffix))
+                .show_scale(i == 0),
+        );
+    }
+
+    let heatmap_layout = Layout::new()
+        .title(Title::new(&format!(
+            "Messages per week, by weekday and hour ({}).",
+            grids
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<&str>>()
+                .join(", then ")
+        )))
+        .grid(
+            LayoutGrid::new()
+                .rows(grids.len())
+                .columns(1)
+                .pattern(GridPattern::Independent),
+        )
+        .height(300 * grids.len());
+
+    heatmap_plot.set_layout(heatmap_layout);
+
+    return heatmap_plot;
+}
+
 pub fn get_hour_plot(dates: &HashMap<String, Vec<i64>>) -> Plot {
     let mut date_plot = Plot::new();
 
@@ -65,19 +543,92 @@ pub fn get_response_time_plot(responses_time: &HashMap<String, Vec<i64>>) -> Plo
     let mut responses_time_plot = Plot::new();
     for (name, times) in responses_time.iter() {
         responses_time_plot.add_trace(
-            Histogram::new(times.to_vec())
+            Histogram::new(without_outliers(times))
                 .x_axis("Time")
                 .y_axis("Count")
                 .name(name),
         );
     }
-    let time_layout = Layout::new().title(Title::new("Average response time of messages."));
+    let time_layout = Layout::new().title(Title::new(
+        "Response time of messages within a session, in seconds (outliers removed).",
+    ));
 
     responses_time_plot.set_layout(time_layout);
 
     return responses_time_plot;
 }
 
+pub fn get_reply_survival_plot(survival: &HashMap<String, SurvivalCurve>) -> Plot {
+    let mut survival_plot = Plot::new();
+    let mut names: Vec<&String> = survival.keys().collect();
+    names.sort();
+
+    for name in names {
+        let curve = &survival[name];
+        // A log axis has no 0: the points at 0s are left out and every curve starts at the
+        // shortest positive reply time with the values of the last point at 0s, or with all
+        // the messages unanswered when there is none.
+        let zero = curve.points.iter().rev().find(|point| point.time_s <= 0.0);
+        let points: Vec<&SurvivalPoint> = curve
+            .points
+            .iter()
+            .filter(|point| point.time_s > 0.0)
+            .collect();
+        let start = points.first().map(|point| point.time_s);
+        let times: Vec<f64> = start
+            .into_iter()
+            .chain(points.iter().map(|point| point.time_s))
+            .collect();
+        let series = |value: fn(&SurvivalPoint) -> f64| -> Vec<f64> {
+            start
+                .map(|_| zero.map_or(1.0, value))
+                .into_iter()
+                .chain(points.iter().map(|point| value(point)))
+                .collect()
+        };
+
+        survival_plot.add_trace(
+            Scatter::new(times.clone(), series(|point| point.lower))
+                .mode(Mode::Lines)
+                .line(Line::new().shape(LineShape::Hv).width(0.0))
+                .legend_group(name)
+                .show_legend(false)
+                .name(&format!("{} (95% lower)", name)),
+        );
+        survival_plot.add_trace(
+            Scatter::new(times.clone(), series(|point| point.upper))
+                .mode(Mode::Lines)
+                .line(Line::new().shape(LineShape::Hv).width(0.0))
+                .fill(Fill::ToNextY)
+                .opacity(0.2)
+                .legend_group(name)
+                .show_legend(false)
+                .name(&format!("{} (95% upper)", name)),
+        );
+        survival_plot.add_trace(
+            Scatter::new(times, series(|point| point.survival))
+                .mode(Mode::Lines)
+                .line(Line::new().shape(LineShape::Hv))
+                .legend_group(name)
+                .name(&match curve.median_s {
+                    Some(median) => format!("{} (median {:.0}s)", name, median),
+                    None => name.clone(),
+                }),
+        );
+    }
+
+    let survival_layout = Layout::new()
+        .title(Title::new(
+            "Probability that a message is still unanswered after t seconds.",
+        ))
+        .x_axis(Axis::new().type_(AxisType::Log))
+        .y_axis(Axis::new().range(vec![0.0, 1.0]));
+
+    survival_plot.set_layout(survival_layout);
+
+    return survival_plot;
+}
+
 pub fn get_message_num_plot(messages_num: &HashMap<String, Vec<i64>>) -> Plot {
     let mut messages_num_plot = Plot::new();
     for (name, times) in messages_num.iter() {
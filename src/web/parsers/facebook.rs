@@ -1,114 +1,37 @@
-use std::collections::HashMap;
+use crate::analysis::filter::MessageFilter;
+use crate::analysis::report::{get_facebook_report, ReportOptions};
+use crate::web::parsers::{get_filter, AttributionQuiz, ReportView, TalkLike, TermTracker};
 
-use crate::parsers::base::{
-    get_frequent_words, get_message_counts, get_message_response_times, get_messages_length,
-    get_messages_num, get_send_hours,
-};
-use crate::parsers::facebook::{get_reactions_counts, parse_facebook};
-
-use plotly::Plot;
-
-use leptos::html::Input;
+use leptos::html::{Input, Textarea};
 use leptos::{
-    component, create_action, create_node_ref, create_resource, create_signal, logging, view, For,
-    IntoView, SignalGet, Suspense,
+    component, create_node_ref, create_resource, create_signal, logging, view, For, IntoView,
+    SignalGet, Suspense,
 };
 
-use crate::plots::web::{
-    get_hour_plot, get_message_count_plot, get_message_length_plot, get_message_num_plot,
-    get_reaction_count_plot, get_response_time_plot,
-};
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{File, SubmitEvent};
 
 #[component]
-fn MessengerData(data: Option<Vec<String>>) -> impl IntoView {
-    let mut words_count: HashMap<String, Vec<String>> = HashMap::new();
-
-    match data {
-        Some(facebook_data) => {
-            let (messages, participants) = parse_facebook(facebook_data);
-            let hour_plotted = create_action(|input: &Plot| {
-                let input = input.to_owned();
-                async move { plotly::bindings::new_plot("HourPlot", &input).await }
-            });
-
-            let msg_plotted = create_action(|input: &Plot| {
-                let input = input.to_owned();
-                async move { plotly::bindings::new_plot("MsgPlot", &input).await }
-            });
-
-            let reaction_plotted = create_action(|input: &Plot| {
-                let input = input.to_owned();
-                async move { plotly::bindings::new_plot("ReactionPlot", &input).await }
-            });
-
-            let responses_time_plotted = create_action(|input: &Plot| {
-                let input = input.to_owned();
-                async move { plotly::bindings::new_plot("ResponsesTimePlot", &input).await }
-            });
-
-            let message_len_plotted = create_action(|input: &Plot| {
-                let input = input.to_owned();
-                async move { plotly::bindings::new_plot("MessageLenPlot", &input).await }
-            });
-
-            let message_number_plotted = create_action(|input: &Plot| {
-                let input = input.to_owned();
-                async move { plotly::bindings::new_plot("MessageNumPlot", &input).await }
-            });
-
-            let reaction_plot = get_reaction_count_plot(&get_reactions_counts(&messages));
-
-            let base_messages = messages.into_iter().map(|m| m.into()).collect();
-
-            let msg_plot = get_message_count_plot(&get_message_counts(&base_messages));
-            let hour_plot = get_hour_plot(&get_send_hours(&base_messages, &participants));
-            words_count = get_frequent_words(&base_messages, 15);
-            let responses_time_plot =
-                get_response_time_plot(&get_message_response_times(&base_messages, &participants));
-            let message_len_plot = get_message_length_plot(&get_messages_length(&base_messages));
-            let message_number_plot = get_message_num_plot(&get_messages_num(&base_messages));
-
-            hour_plotted.dispatch(hour_plot);
-            reaction_plotted.dispatch(reaction_plot);
-            msg_plotted.dispatch(msg_plot);
-            responses_time_plotted.dispatch(responses_time_plot);
-            message_len_plotted.dispatch(message_len_plot);
-            message_number_plotted.dispatch(message_number_plot);
-        }
-        _ => {}
-    }
+fn MessengerData(
+    data: Option<Vec<String>>,
+    extra_stop_words: String,
+    filter: MessageFilter,
+) -> impl IntoView {
+    let options = ReportOptions {
+        extra_stop_words,
+        ..ReportOptions::default()
+    };
+    let (report, messages) = data
+        .map(|facebook_data| get_facebook_report(facebook_data, &filter, &options))
+        .map_or((None, vec![]), |(report, messages)| {
+            (Some(report), messages)
+        });
 
     view! {
-        <div>
-            <div id="HourPlot"></div>
-            <div id="MsgPlot"></div>
-            <div id="ReactionPlot"></div>
-            <div id="ResponsesTimePlot"></div>
-            <div id="MessageNumPlot"></div>
-            <div id="MessageLenPlot"></div>
-            <div id="Words">
-                <For
-                    each=move || words_count.clone()
-                    key = |words_count| words_count.0.clone()
-                    children = move |words_count| {
-                        view! {
-                                <p>{words_count.0}</p>
-                                <For
-                                    each = move || words_count.1.clone()
-                                    key = |word| word.clone()
-                                    children = move |word| {
-                                        view! {
-                                            <p>{word}</p>
-                                        }
-                                    }
-                                />
-                        }
-                    }
-                />
-            </div>
-        </div>
+        <ReportView report=report/>
+        <TermTracker messages=messages.clone()/>
+        <AttributionQuiz messages=messages.clone()/>
+        <TalkLike messages=messages/>
     }
 }
 
@@ -129,12 +52,36 @@ async fn on_files_selected(files: Vec<File>) -> Vec<String> {
 #[component]
 pub fn FacebookMultiFileSelectorComponent() -> impl IntoView {
     let (files, set_files) = create_signal(Vec::<File>::new());
+    let (stop_words, set_stop_words) = create_signal(String::new());
+    let (filter, set_filter) = create_signal(MessageFilter::default());
+    let (filter_error, set_filter_error) = create_signal(None::<String>);
     let texts = create_resource(files, on_files_selected);
 
     let input_element = create_node_ref::<Input>();
+    let stop_words_element = create_node_ref::<Textarea>();
+    let since_element = create_node_ref::<Input>();
+    let until_element = create_node_ref::<Input>();
+    let participants_element = create_node_ref::<Input>();
+    let match_element = create_node_ref::<Input>();
 
     let on_files_submit = move |ev: SubmitEvent| {
         ev.prevent_default();
+        set_stop_words(stop_words_element().expect("<textarea> to exist").value());
+        match get_filter(
+            &since_element().expect("<input> to exist").value(),
+            &until_element().expect("<input> to exist").value(),
+            &participants_element().expect("<input> to exist").value(),
+            &match_element().expect("<input> to exist").value(),
+        ) {
+            Ok(new_filter) => {
+                set_filter_error(None);
+                set_filter(new_filter);
+            }
+            Err(error) => {
+                set_filter_error(Some(error.to_string()));
+                return;
+            }
+        }
         let file_list = input_element().expect("<input> to exist").files();
         let mut files = Vec::<File>::new();
 
@@ -164,8 +111,21 @@ pub fn FacebookMultiFileSelectorComponent() -> impl IntoView {
 
         <form on:submit=on_files_submit>
             <input type="file" multiple node_ref=input_element/>
+            <textarea
+                placeholder="Additional stop words, one per line"
+                node_ref=stop_words_element
+            ></textarea>
+            <label>"Since " <input type="date" node_ref=since_element/></label>
+            <label>"Until " <input type="date" node_ref=until_element/></label>
+            <input
+                type="text"
+                placeholder="Participants, separated by commas"
+                node_ref=participants_element
+            />
+            <input type="text" placeholder="Regular expression on the text" node_ref=match_element/>
             <input type="submit" value="Submit"/>
         </form>
+        {move || filter_error.get().map(|error| view! { <p class="error">"Invalid regular expression: " {error}</p> })}
 
         <p>"Selected files: " {move || files.get().iter().map(|f| f.name()).collect::<Vec<String>>()}</p>
 
@@ -177,7 +137,7 @@ pub fn FacebookMultiFileSelectorComponent() -> impl IntoView {
         </div>
 
         <Suspense fallback = move || view! {<p>"Loading..."</p>}>
-            <MessengerData data={texts.get()}/>
+            <MessengerData data={texts.get()} extra_stop_words={stop_words.get()} filter={filter.get()}/>
         </Suspense>
     }
 }
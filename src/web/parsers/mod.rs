@@ -2,8 +2,43 @@
 pub mod facebook;
 pub mod whatsapp;
 
-use leptos::{ component, view, IntoView};
+use crate::analysis::attribution::{AttributionModel, AttributionStats, DEFAULT_TEST_EVERY};
+use crate::analysis::distinctive::DistinctiveTerms;
+use crate::analysis::filter::MessageFilter;
+use crate::analysis::graph::InteractionGraph;
+use crate::analysis::markov::{generate_messages, get_markov_chains, DEFAULT_MAX_WORDS, DEFAULT_ORDER};
+use crate::analysis::milestones::Milestone;
+use crate::analysis::ngrams::PhraseStats;
+use crate::analysis::reacted::ReactedToStats;
+use crate::analysis::report::{ConversationReport, MessengerActivity};
+use crate::analysis::stats::BoxSummary;
+use crate::analysis::style::WritingStyle;
+use crate::analysis::summary::PeriodSummary;
+use crate::analysis::timeline::{format_timestamp, Granularity};
+use crate::analysis::tracking::{track_terms, Term};
+use crate::analysis::turns::TurnTaking;
+use crate::analysis::unanswered::UnansweredStats;
+use crate::parsers::base::BaseMessage;
+use crate::plots::web::{
+    get_activity_heatmap_plot, get_domain_plot, get_emoji_plot, get_emoji_timeline_plot,
+    get_hour_plot, get_interaction_graph_plot, get_media_plot, get_media_timeline_plot,
+    get_message_count_plot, get_message_length_plot, get_message_num_plot,
+    get_reaction_breakdown_plot, get_reaction_count_plot, get_reaction_matrix_plot,
+    get_reaction_rate_plot, get_reaction_reciprocity_plot, get_reply_survival_plot,
+    get_response_time_plot, get_sentiment_plot, get_term_tracking_plot, get_timeline_plot,
+    get_topic_participant_plot, get_topic_plot,
+};
+use chrono::DateTime;
+use leptos::html::Input;
+use leptos::{ component, create_action, create_node_ref, create_signal, store_value, view, For, IntoView, SignalGet, WriteSignal};
+use plotly::Plot;
+use std::collections::{HashMap, HashSet};
 use leptos_router::{A,Outlet} ;
+use regex::Regex;
+use stylers::style;
+use js_sys::{Function, Reflect};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::SubmitEvent;
 
 
 #[component]
@@ -24,3 +59,742 @@ pub fn Parsers() -> impl IntoView {
         </div>
     }
 }
+
+/// Filter from the dashboard inputs, empty inputs (and invalid dates) keep everything.
+/// Participants are separated by commas, an invalid regular expression is an error.
+pub fn get_filter(
+    since: &str,
+    until: &str,
+    participants: &str,
+    content: &str,
+) -> Result<MessageFilter, regex::Error> {
+    let participants: HashSet<String> = participants
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect();
+
+    return Ok(MessageFilter {
+        since: since.parse().ok(),
+        until: until.parse().ok(),
+        participants: if participants.is_empty() { None } else { Some(participants) },
+        content: if content.is_empty() { None } else { Some(Regex::new(content)?) },
+        ..MessageFilter::default()
+    });
+}
+
+/// Render the plot, from wasm to JS to the DIV with this id in the view.
+fn render_plot(div_id: &'static str, plot: Plot) {
+    let plotted = create_action(move |input: &Plot| {
+        let input = input.to_owned();
+        async move { plotly::bindings::new_plot(div_id, &input).await }
+    });
+    plotted.dispatch(plot);
+}
+
+/// Like `render_plot`, then send the x value of every clicked point to `on_click`.
+fn render_clickable_plot(div_id: &'static str, plot: Plot, on_click: WriteSignal<Option<String>>) {
+    let plotted = create_action(move |input: &Plot| {
+        let input = input.to_owned();
+        async move {
+            plotly::bindings::new_plot(div_id, &input).await;
+            let div = match leptos::document().get_element_by_id(div_id) {
+                Some(div) => div,
+                None => return,
+            };
+            // Plotly adds `on` to the DIV, the event has the clicked points.
+            let handler = Closure::<dyn Fn(JsValue)>::new(move |event: JsValue| {
+                let x = Reflect::get(&event, &"points".into())
+                    .and_then(|points| Reflect::get(&points, &0.into()))
+                    .and_then(|point| Reflect::get(&point, &"x".into()))
+                    .ok()
+                    .and_then(|x| x.as_string());
+                if let Some(x) = x {
+                    on_click(Some(x));
+                }
+            });
+            if let Ok(on) = Reflect::get(&div, &"on".into()) {
+                let _ = on.unchecked_into::<Function>().call2(&div, &"plotly_click".into(), handler.as_ref());
+            }
+            // The handler lives as long as the plot.
+            handler.forget();
+        }
+    });
+    plotted.dispatch(plot);
+}
+
+/// Ids of the DIVs of the timelines of the default `ReportOptions`.
+const TIMELINE_DIV_IDS: [&str; 2] = ["TimelinePlot", "MonthlyTimelinePlot"];
+
+/// Every plot and table of a report, shared by the Facebook and WhatsApp pages.
+#[component]
+pub fn ReportView(report: Option<ConversationReport>) -> impl IntoView {
+    // Day of the timeline bucket clicked last.
+    let (clicked_day, set_clicked_day) = create_signal(None::<String>);
+    if let Some(report) = &report {
+        for (div_id, timeline) in TIMELINE_DIV_IDS.into_iter().zip(report.timelines.iter()) {
+            render_clickable_plot(div_id, get_timeline_plot(timeline), set_clicked_day);
+        }
+        render_plot("HourPlot", get_hour_plot(&report.send_hours));
+        render_plot("ActivityHeatmapPlot", get_activity_heatmap_plot(&report.activity_heatmap));
+        render_plot("MsgPlot", get_message_count_plot(&report.message_counts));
+        if let Some(reactions) = &report.reactions {
+            render_plot("ReactionPlot", get_reaction_count_plot(&reactions.counts));
+            render_plot("ReactionBreakdownPlot", get_reaction_breakdown_plot(&reactions.breakdown));
+            render_plot("ReactionMatrixPlot", get_reaction_matrix_plot(&reactions.matrix));
+        }
+        if let Some(reacted_to) = &report.reacted_to {
+            render_plot("ReactionLengthPlot", get_reaction_rate_plot(&reacted_to.by_length, "Reacted messages by length (%)."));
+            render_plot("ReactionHourPlot", get_reaction_rate_plot(&reacted_to.by_hour, "Reacted messages by hour (%)."));
+            render_plot("ReactionContentPlot", get_reaction_rate_plot(&reacted_to.by_content, "Reacted messages by content (%)."));
+            render_plot("ReactionReciprocityPlot", get_reaction_reciprocity_plot(&reacted_to.reciprocity));
+        }
+        render_plot("ResponsesTimePlot", get_response_time_plot(&report.response_times));
+        render_plot("ReplySurvivalPlot", get_reply_survival_plot(&report.reply_survival));
+        render_plot("InteractionGraphPlot", get_interaction_graph_plot(&report.interactions));
+        render_plot("MessageNumPlot", get_message_num_plot(&report.turns.runs));
+        render_plot("MessageLenPlot", get_message_length_plot(&report.message_lengths));
+        render_plot("EmojiPlot", get_emoji_plot(&report.emojis));
+        render_plot("EmojiTimelinePlot", get_emoji_timeline_plot(&report.emojis));
+        render_plot("MediaPlot", get_media_plot(&report.media));
+        render_plot("MediaTimelinePlot", get_media_timeline_plot(&report.media));
+        render_plot("DomainPlot", get_domain_plot(&report.media));
+        render_plot("SentimentPlot", get_sentiment_plot(&report.sentiment));
+        render_plot("TopicPlot", get_topic_plot(&report.topics));
+        render_plot("TopicParticipantPlot", get_topic_participant_plot(&report.topics));
+    }
+    let report = report.unwrap_or_default();
+    let words_count = report.frequent_words;
+    let summaries = report.summaries;
+
+    view! {
+        <div>
+            <div id="TimelinePlot"></div>
+            <div id="MonthlyTimelinePlot"></div>
+            {move || clicked_day().map(|day| view! { <PeriodSummaryView summaries=summaries.clone() day=day/> })}
+            <MilestonesTimeline milestones=report.milestones/>
+            <div id="HourPlot"></div>
+            <div id="ActivityHeatmapPlot"></div>
+            <div id="MsgPlot"></div>
+            <div id="ReactionPlot"></div>
+            <div id="ReactionBreakdownPlot"></div>
+            <div id="ReactionMatrixPlot"></div>
+            <div id="ReactionLengthPlot"></div>
+            <div id="ReactionHourPlot"></div>
+            <div id="ReactionContentPlot"></div>
+            <div id="ReactionReciprocityPlot"></div>
+            {report.reacted_to.map(|reacted_to| view! { <ReactedToTable reacted_to=reacted_to/> })}
+            <div id="ResponsesTimePlot"></div>
+            <ResponseTimeTable summaries=report.response_time_summaries/>
+            <div id="ReplySurvivalPlot"></div>
+            <div id="InteractionGraphPlot"></div>
+            <CentralityTable graph=report.interactions/>
+            <div id="MessageNumPlot"></div>
+            <TurnTakingTable turns=report.turns/>
+            <UnansweredTable unanswered=report.unanswered/>
+            {report.messenger_activity.map(|activity| view! { <MessengerActivityTable activity=activity/> })}
+            <div id="MessageLenPlot"></div>
+            <StyleTable styles=report.styles/>
+            <AttributionTable attribution=report.attribution/>
+            <div id="EmojiPlot"></div>
+            <div id="EmojiTimelinePlot"></div>
+            <div id="MediaPlot"></div>
+            <div id="MediaTimelinePlot"></div>
+            <div id="DomainPlot"></div>
+            <div id="SentimentPlot"></div>
+            <div id="TopicPlot"></div>
+            <div id="TopicParticipantPlot"></div>
+            <div id="Words">
+                <p>"Detected languages: " {report.languages.join(", ")}</p>
+                <For
+                    each=move || words_count.clone()
+                    key = |words_count| words_count.0.clone()
+                    children = move |words_count| {
+                        view! {
+                                <p>{words_count.0}</p>
+                                <For
+                                    each = move || words_count.1.clone()
+                                    key = |word| word.clone()
+                                    children = move |word| {
+                                        view! {
+                                            <p>{word}</p>
+                                        }
+                                    }
+                                />
+                        }
+                    }
+                />
+                <DistinctiveWordsTable words=report.distinctive_words bigrams=report.distinctive_bigrams/>
+                <PhrasesTable phrases=report.phrases/>
+            </div>
+        </div>
+    }
+}
+
+#[component]
+pub fn TurnTakingTable(turns: TurnTaking) -> impl IntoView {
+    let mut names: Vec<String> = turns.runs.keys().cloned().collect();
+    names.sort();
+
+    view! {
+        <div id="TurnTaking">
+            <p>{format!("Average turns per session: {:.1}", turns.turns_per_session)}</p>
+            <table>
+                <tr>
+                    <th>"Participant"</th>
+                    <th>"Double texting"</th>
+                    <th>"Triple texting"</th>
+                </tr>
+                {
+                    names.into_iter().map(|name| view! {
+                        <tr>
+                            <td>{ name.clone() }</td>
+                            <td>{ format!("{:.1}%", turns.double_text_rate[&name] * 100.0) }</td>
+                            <td>{ format!("{:.1}%", turns.triple_text_rate[&name] * 100.0) }</td>
+                        </tr>
+                    }).collect::<Vec<_>>()
+                }
+            </table>
+        </div>
+    }
+}
+
+#[component]
+pub fn DistinctiveWordsTable(words: DistinctiveTerms, bigrams: DistinctiveTerms) -> impl IntoView {
+    let mut names: Vec<String> = words.log_odds.keys().cloned().collect();
+    names.sort();
+
+    let format_terms = |terms: Option<&Vec<(String, f64)>>| -> Vec<String> {
+        terms
+            .map(|terms| terms.iter().map(|(term, score)| format!("{} ({:.2})", term, score)).collect())
+            .unwrap_or_default()
+    };
+
+    view! {
+        <div id="DistinctiveWords">
+            {
+                names.into_iter().map(|name| {
+                    let columns = [
+                        format_terms(words.tf_idf.get(&name)),
+                        format_terms(words.log_odds.get(&name)),
+                        format_terms(bigrams.log_odds.get(&name)),
+                    ];
+                    let num_rows = columns.iter().map(|column| column.len()).max().unwrap_or(0);
+
+                    view! {
+                        <p>{ format!("{} distinctive words", name) }</p>
+                        <table>
+                            <tr>
+                                <th>"TF-IDF"</th>
+                                <th>"Log-odds"</th>
+                                <th>"Bigrams (log-odds)"</th>
+                            </tr>
+                            {
+                                (0..num_rows).map(|row| view! {
+                                    <tr>
+                                        {
+                                            columns.iter().map(|column| view! {
+                                                <td>{ column.get(row).cloned().unwrap_or_default() }</td>
+                                            }).collect::<Vec<_>>()
+                                        }
+                                    </tr>
+                                }).collect::<Vec<_>>()
+                            }
+                        </table>
+                    }
+                }).collect::<Vec<_>>()
+            }
+        </div>
+    }
+}
+
+#[component]
+pub fn PhrasesTable(phrases: PhraseStats) -> impl IntoView {
+    let mut names: Vec<String> = phrases.bigrams.per_participant.keys().cloned().collect();
+    names.sort();
+
+    let format_counts = |counts: Option<&Vec<(String, i64)>>| -> Vec<String> {
+        counts
+            .map(|counts| counts.iter().map(|(phrase, count)| format!("{} ({})", phrase, count)).collect())
+            .unwrap_or_default()
+    };
+
+    view! {
+        <div id="Phrases">
+            {
+                names.into_iter().map(|name| {
+                    let collocations: Vec<String> = phrases.collocations.per_participant.get(&name)
+                        .map(|collocations| collocations.iter().map(|c| format!("{} ({})", c.bigram, c.count)).collect())
+                        .unwrap_or_default();
+                    let columns = [
+                        format_counts(phrases.bigrams.per_participant.get(&name)),
+                        format_counts(phrases.trigrams.per_participant.get(&name)),
+                        collocations,
+                        format_counts(phrases.catchphrases.get(&name)),
+                    ];
+                    let num_rows = columns.iter().map(|column| column.len()).max().unwrap_or(0);
+
+                    view! {
+                        <p>{ format!("{} phrases", name) }</p>
+                        <table>
+                            <tr>
+                                <th>"Bigrams"</th>
+                                <th>"Trigrams"</th>
+                                <th>"Collocations"</th>
+                                <th>"Catchphrases"</th>
+                            </tr>
+                            {
+                                (0..num_rows).map(|row| view! {
+                                    <tr>
+                                        {
+                                            columns.iter().map(|column| view! {
+                                                <td>{ column.get(row).cloned().unwrap_or_default() }</td>
+                                            }).collect::<Vec<_>>()
+                                        }
+                                    </tr>
+                                }).collect::<Vec<_>>()
+                            }
+                        </table>
+                    }
+                }).collect::<Vec<_>>()
+            }
+        </div>
+    }
+}
+
+#[component]
+pub fn ResponseTimeTable(summaries: HashMap<String, BoxSummary>) -> impl IntoView {
+    let mut names: Vec<String> = summaries.keys().cloned().collect();
+    names.sort();
+
+    view! {
+        <table id="ResponseTimeTable">
+            <tr>
+                <th>"Participant"</th>
+                <th>"Median (s)"</th>
+                <th>"Quartiles (s)"</th>
+                <th>"Trimmed mean (s)"</th>
+                <th>"Outliers"</th>
+            </tr>
+            {
+                names.into_iter().map(|name| {
+                    let summary = &summaries[&name];
+                    view! {
+                        <tr>
+                            <td>{ name.clone() }</td>
+                            <td>{ format!("{:.0}", summary.median) }</td>
+                            <td>{ format!("{:.0} - {:.0}", summary.q1, summary.q3) }</td>
+                            <td>{ format!("{:.0}", summary.trimmed_mean) }</td>
+                            <td>{ format!("{} / {}", summary.num_outliers, summary.count) }</td>
+                        </tr>
+                    }
+                }).collect::<Vec<_>>()
+            }
+        </table>
+    }
+}
+
+#[component]
+pub fn UnansweredTable(unanswered: UnansweredStats) -> impl IntoView {
+    let mut names: Vec<String> = unanswered.unanswered_rate.keys().cloned().collect();
+    names.sort();
+
+    view! {
+        <div id="Unanswered">
+            <table>
+                <tr>
+                    <th>"Participant"</th>
+                    <th>"Unanswered bursts"</th>
+                    <th>"Questions"</th>
+                    <th>"Questions answered"</th>
+                </tr>
+                {
+                    names.into_iter().map(|name| view! {
+                        <tr>
+                            <td>{ name.clone() }</td>
+                            <td>{ format!("{} ({:.1}%)", unanswered.unanswered_bursts[&name], unanswered.unanswered_rate[&name] * 100.0) }</td>
+                            <td>{ unanswered.questions[&name] }</td>
+                            <td>{ unanswered.question_answer_rate.get(&name).map(|rate| format!("{:.1}%", rate * 100.0)).unwrap_or_default() }</td>
+                        </tr>
+                    }).collect::<Vec<_>>()
+                }
+            </table>
+            <p>"Longest silences"</p>
+            <table>
+                <tr>
+                    <th>"Days"</th>
+                    <th>"Last message"</th>
+                    <th>"Broken by"</th>
+                </tr>
+                {
+                    unanswered.longest_silences.iter().map(|silence| view! {
+                        <tr>
+                            <td>{ format!("{:.1}", silence.duration_ms as f64 / (24.0 * 60.0 * 60.0 * 1000.0)) }</td>
+                            <td>{ format!("{}: {}", silence.sender_name, silence.content.clone().unwrap_or_default()) }</td>
+                            <td>{ silence.broken_by.clone() }</td>
+                        </tr>
+                    }).collect::<Vec<_>>()
+                }
+            </table>
+        </div>
+    }
+}
+
+#[component]
+pub fn MessengerActivityTable(activity: MessengerActivity) -> impl IntoView {
+    let mut names: Vec<String> = activity.unsent_rate.keys().cloned().collect();
+    names.sort();
+
+    view! {
+        <table id="MessengerActivityTable">
+            <tr>
+                <th>"Participant"</th>
+                <th>"Calls started"</th>
+                <th>"Missed calls"</th>
+                <th>"Total call time (min)"</th>
+                <th>"Median call time (min)"</th>
+                <th>"Unsent messages"</th>
+                <th>"Stickers"</th>
+            </tr>
+            {
+                names.into_iter().map(|name| view! {
+                    <tr>
+                        <td>{ name.clone() }</td>
+                        <td>{ activity.calls.get(&name).copied().unwrap_or(0) }</td>
+                        <td>{ activity.missed_calls.get(&name).copied().unwrap_or(0) }</td>
+                        <td>{ format!("{:.0}", activity.total_call_duration_s.get(&name).copied().unwrap_or(0) as f64 / 60.0) }</td>
+                        <td>{ activity.median_call_duration_s.get(&name).map(|median| format!("{:.1}", median / 60.0)).unwrap_or_default() }</td>
+                        <td>{ format!("{:.2}%", activity.unsent_rate[&name] * 100.0) }</td>
+                        <td>{ activity.stickers.get(&name).copied().unwrap_or(0) }</td>
+                    </tr>
+                }).collect::<Vec<_>>()
+            }
+        </table>
+    }
+}
+
+#[component]
+pub fn ReactedToTable(reacted_to: ReactedToStats) -> impl IntoView {
+    view! {
+        <div id="ReactedTo">
+            <p>{ format!("{:.1}% of the messages got a reaction.", reacted_to.overall_rate * 100.0) }</p>
+            <p>"Most reacted messages"</p>
+            <table>
+                <tr>
+                    <th>"Sender"</th>
+                    <th>"Message"</th>
+                    <th>"Reactions"</th>
+                </tr>
+                {
+                    reacted_to.top_messages.iter().map(|msg| view! {
+                        <tr>
+                            <td>{ msg.sender_name.clone() }</td>
+                            <td>{ msg.content.clone().unwrap_or_else(|| "(media)".to_string()) }</td>
+                            <td>{ msg.actors.join(", ") }</td>
+                        </tr>
+                    }).collect::<Vec<_>>()
+                }
+            </table>
+            <p>"Words that get reactions"</p>
+            <table>
+                <tr>
+                    <th>"Word"</th>
+                    <th>"Messages"</th>
+                    <th>"Reacted"</th>
+                    <th>"Lift"</th>
+                </tr>
+                {
+                    reacted_to.top_words.iter().map(|word| view! {
+                        <tr>
+                            <td>{ word.word.clone() }</td>
+                            <td>{ word.messages }</td>
+                            <td>{ format!("{:.1}%", word.rate * 100.0) }</td>
+                            <td>{ format!("{:.2}", word.lift) }</td>
+                        </tr>
+                    }).collect::<Vec<_>>()
+                }
+            </table>
+        </div>
+    }
+}
+
+#[component]
+pub fn CentralityTable(graph: InteractionGraph) -> impl IntoView {
+    view! {
+        <table id="CentralityTable">
+            <tr>
+                <th>"Participant"</th>
+                <th>"Messages"</th>
+                <th>"Answered by others"</th>
+                <th>"Answers others"</th>
+                <th>"Degree centrality"</th>
+                <th>"PageRank"</th>
+            </tr>
+            {
+                graph.nodes.into_iter().map(|node| view! {
+                    <tr>
+                        <td>{ node.name }</td>
+                        <td>{ node.messages }</td>
+                        <td>{ node.in_strength }</td>
+                        <td>{ node.out_strength }</td>
+                        <td>{ format!("{:.2}", node.degree_centrality) }</td>
+                        <td>{ format!("{:.3}", node.pagerank) }</td>
+                    </tr>
+                }).collect::<Vec<_>>()
+            }
+        </table>
+    }
+}
+
+/// Search box plotting the weekly usage of comma separated words, phrases or /expressions/.
+#[component]
+pub fn TermTracker(messages: Vec<BaseMessage>) -> impl IntoView {
+    let (terms, set_terms) = create_signal(String::new());
+    let terms_element = create_node_ref::<Input>();
+
+    let on_terms_submit = move |ev: SubmitEvent| {
+        ev.prevent_default();
+        set_terms(terms_element().expect("<input> to exist").value());
+    };
+
+    view! {
+        <form on:submit=on_terms_submit>
+            <input
+                type="text"
+                placeholder="Words, phrases or /expressions/, separated by commas"
+                node_ref=terms_element
+            />
+            <input type="submit" value="Track"/>
+        </form>
+        {move || view! { <TermTrackingView messages=messages.clone() terms=terms.get()/> }}
+    }
+}
+
+#[component]
+fn TermTrackingView(messages: Vec<BaseMessage>, terms: String) -> impl IntoView {
+    let terms: Vec<(String, Term)> = terms
+        .split(',')
+        .filter_map(|text| Term::parse(text).map(|term| (text.trim().to_string(), term)))
+        .collect();
+    let tracking = track_terms(&messages, &terms, Granularity::Week);
+    if !terms.is_empty() {
+        render_plot("TermTrackingPlot", get_term_tracking_plot(&tracking));
+    }
+    let date = |timestamp_ms: i64| {
+        DateTime::from_timestamp_millis(timestamp_ms)
+            .map(|datetime| datetime.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    };
+
+    view! {
+        <div id="TermTrackingPlot"></div>
+        <table id="TermTrackingTable">
+            <tr>
+                <th>"Term"</th>
+                <th>"Uses"</th>
+                <th>"First use"</th>
+                <th>"Last use"</th>
+            </tr>
+            {
+                tracking.terms.into_iter().map(|usage| view! {
+                    <tr>
+                        <td>{ usage.term }</td>
+                        <td>{ usage.total }</td>
+                        <td>{ usage.first.map(|first| format!("{} {}: {}", date(first.timestamp_ms), first.sender_name, first.content)).unwrap_or_default() }</td>
+                        <td>{ usage.last.map(|last| format!("{} {}: {}", date(last.timestamp_ms), last.sender_name, last.content)).unwrap_or_default() }</td>
+                    </tr>
+                }).collect::<Vec<_>>()
+            }
+        </table>
+    }
+}
+
+#[component]
+pub fn StyleTable(styles: HashMap<String, WritingStyle>) -> impl IntoView {
+    let mut names: Vec<String> = styles.keys().cloned().collect();
+    names.sort();
+    let columns: Vec<Vec<(&str, String)>> = names.iter().map(|name| styles[name].metrics()).collect();
+    let labels: Vec<&str> = WritingStyle::default().metrics().into_iter().map(|(label, _)| label).collect();
+
+    view! {
+        <table id="StyleTable">
+            <tr>
+                <th>"Writing style"</th>
+                { names.into_iter().map(|name| view! { <th>{ name }</th> }).collect::<Vec<_>>() }
+            </tr>
+            {
+                labels.into_iter().enumerate().map(|(idx, label)| view! {
+                    <tr>
+                        <td>{ label }</td>
+                        { columns.iter().map(|metrics| view! { <td>{ metrics[idx].1.clone() }</td> }).collect::<Vec<_>>() }
+                    </tr>
+                }).collect::<Vec<_>>()
+            }
+        </table>
+    }
+}
+
+#[component]
+pub fn AttributionTable(attribution: AttributionStats) -> impl IntoView {
+    let mut names: Vec<String> = attribution.participant_accuracy.keys().cloned().collect();
+    names.sort();
+
+    view! {
+        <div id="AttributionTable">
+            <p>{format!("Author guessed right for {:.1}% of {} held out texts", attribution.accuracy * 100.0, attribution.num_tested)}</p>
+            <table>
+                <tr>
+                    <th>"Participant"</th>
+                    <th>"Guessed right"</th>
+                    <th>"Most typical character sequences"</th>
+                </tr>
+                {
+                    names.into_iter().map(|name| view! {
+                        <tr>
+                            <td>{ name.clone() }</td>
+                            <td>{ format!("{:.1}%", attribution.participant_accuracy[&name] * 100.0) }</td>
+                            <td>{ attribution.features.get(&name).map(|features| features.iter().map(|(ngram, _)| format!("{:?}", ngram)).collect::<Vec<_>>().join(" ")).unwrap_or_default() }</td>
+                        </tr>
+                    }).collect::<Vec<_>>()
+                }
+            </table>
+        </div>
+    }
+}
+
+/// "Who said it?": a random held out text to attribute, then the answer and the guess of the
+/// model, which never saw the text.
+#[component]
+pub fn AttributionQuiz(messages: Vec<BaseMessage>) -> impl IntoView {
+    let texts: Vec<BaseMessage> = messages
+        .into_iter()
+        .filter(|msg| msg.content.as_deref().is_some_and(|text| !text.trim().is_empty()))
+        .collect();
+    let mut names: Vec<String> = texts.iter().map(|msg| msg.sender_name.clone()).collect::<HashSet<String>>().into_iter().collect();
+    names.sort();
+    let model = store_value(AttributionModel::train(
+        texts.iter().enumerate().filter(|(idx, _)| idx % DEFAULT_TEST_EVERY != 0).map(|(_, msg)| msg),
+    ));
+    let questions: Vec<BaseMessage> = texts.into_iter().step_by(DEFAULT_TEST_EVERY).collect();
+    let num_questions = questions.len();
+    let questions = store_value(questions);
+    let pick = move || {
+        if num_questions == 0 {
+            return None;
+        }
+        return Some((js_sys::Math::random() * num_questions as f64) as usize % num_questions);
+    };
+
+    let (question, set_question) = create_signal(pick());
+    let (guess, set_guess) = create_signal(None::<String>);
+
+    view! {
+        <div id="AttributionQuiz">
+            <p>"Who said it?"</p>
+            <p>{move || question().map(|idx| questions.with_value(|questions| questions[idx].content.clone().unwrap_or_default()))}</p>
+            <div>
+                {
+                    names.into_iter().map(|name| {
+                        let guessed = name.clone();
+                        view! { <button on:click=move |_| set_guess(Some(guessed.clone()))>{ name }</button> }
+                    }).collect::<Vec<_>>()
+                }
+            </div>
+            {move || guess().zip(question()).map(|(guess, idx)| {
+                let msg = questions.with_value(|questions| questions[idx].clone());
+                let prediction = model.with_value(|model| model.predict(msg.content.as_deref().unwrap_or_default()));
+                view! {
+                    <p>{ format!("{}, it was {}", if guess == msg.sender_name { "Right" } else { "Wrong" }, msg.sender_name) }</p>
+                    <p>{ format!("The model guessed {}", prediction.iter().map(|(name, probability)| format!("{} ({:.0}%)", name, probability * 100.0)).collect::<Vec<_>>().join(", ")) }</p>
+                }
+            })}
+            <button on:click=move |_| { set_guess(None); set_question(pick()); }>"Next"</button>
+        </div>
+    }
+}
+
+/// Fake messages in the style of each participant, new ones on every click.
+#[component]
+pub fn TalkLike(messages: Vec<BaseMessage>) -> impl IntoView {
+    let chains = store_value(get_markov_chains(&messages, DEFAULT_ORDER));
+    let (seed, set_seed) = create_signal(0u64);
+
+    view! {
+        <div id="TalkLike">
+            <button on:click=move |_| set_seed((js_sys::Math::random() * u32::MAX as f64) as u64)>"Generate"</button>
+            {move || {
+                let generated = chains.with_value(|chains| generate_messages(chains, seed(), 3, DEFAULT_MAX_WORDS));
+                let mut names: Vec<String> = generated.keys().cloned().collect();
+                names.sort();
+                names.into_iter().map(|name| view! {
+                    <p>{ format!("Talk like {}", name) }</p>
+                    <ul>
+                        { generated[&name].iter().map(|text| view! { <li>{ text.clone() }</li> }).collect::<Vec<_>>() }
+                    </ul>
+                }).collect::<Vec<_>>()
+            }}
+        </div>
+    }
+}
+
+/// Summary of the period containing the day, from the summaries of a report.
+#[component]
+fn PeriodSummaryView(summaries: Vec<PeriodSummary>, day: String) -> impl IntoView {
+    // Plotly may add a time to the dates of the x axis.
+    let day: String = day.chars().take(10).collect();
+    let summary = summaries.into_iter().rev().find(|summary| summary.period <= day);
+
+    view! {
+        <div id="PeriodSummary">
+            {
+                summary.map(|summary| view! {
+                    <p>{ format!("Period starting {}: {} messages", summary.period, summary.num_messages) }</p>
+                    <ul>
+                        {
+                            summary.messages.into_iter().map(|msg| view! {
+                                <li>{ format!("{} {}: {}", format_timestamp(msg.timestamp_ms), msg.sender_name, msg.content) }</li>
+                            }).collect::<Vec<_>>()
+                        }
+                    </ul>
+                })
+            }
+        </div>
+    }
+}
+
+/// Milestones on a vertical line, oldest first.
+#[component]
+pub fn MilestonesTimeline(milestones: Vec<Milestone>) -> impl IntoView {
+    let styler_class = style! {
+        ol {
+            list-style: none;
+            border-left: 2px solid #333;
+            margin-left: 10px;
+            padding-left: 20px;
+        }
+        li {
+            margin-bottom: 15px;
+        }
+        .date {
+            color: gray;
+        }
+    };
+
+    view! {
+        class=styler_class,
+        <div id="Milestones">
+            <p>"Milestones"</p>
+            <ol>
+                {
+                    milestones.into_iter().map(|milestone| view! {
+                        <li>
+                            <span class="date">{ format_timestamp(milestone.timestamp_ms) }</span>
+                            <p>{ milestone.title }</p>
+                            <p>{ format!("{}: {}", milestone.sender_name, milestone.content.unwrap_or_default()) }</p>
+                        </li>
+                    }).collect::<Vec<_>>()
+                }
+            </ol>
+        </div>
+    }
+}